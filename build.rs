@@ -0,0 +1,16 @@
+// Expose the git commit the binary was built from as STUB_GIT_HASH, when available.
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output();
+    if let Ok(output) = output
+        && output.status.success()
+    {
+        let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !hash.is_empty() {
+            println!("cargo:rustc-env=STUB_GIT_HASH={}", hash);
+        }
+    }
+}
@@ -1,34 +1,149 @@
 use axum::{
     body::Body,
-    extract::{Multipart, Path},
-    http::{header, HeaderValue, StatusCode},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Multipart, Path, RawQuery,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
-    response::{sse::Event, IntoResponse, Redirect, Response, Sse},
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Redirect, Response, Sse,
+    },
 };
+use std::net::SocketAddr;
 use tokio::fs;
-use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
 
 use crate::tools::{
-    base_config_dir, base_json_dir, collect_json_index, collect_subdir_entries, form_value,
-    html_escape, is_log_ignored, is_safe_rel_path, is_safe_segment, log_line, log_snapshot,
-    normalize_log_pattern, read_log_enabled, read_log_ignore_patterns, read_ping_endpoint,
-    read_refresh_endpoint, read_route_mappings, subscribe_logs, write_route_mappings, RouteMapping,
+    accept_languages, allowed_cors_origin, assertions_snapshot, base_config_dir, base_json_dir,
+    base_url, bump_fs_generation, cached_dashboard, check_expectation, check_rate_limit,
+    collect_json_index, collect_subdir_entries, collect_subdirs, compute_etag, convert_fixture,
+    dangling_route_mappings, derive_fixture_path, etag_matches, fill_refresh_placeholders,
+    form_value, form_value_utf8, form_values, format_json_output, fs_changes_snapshot,
+    fs_generation, generate_from_schema, graphql_operation_keys, has_fixture_extension,
+    highlight_json, html_escape, infer_json_schema, is_enabled_value, is_hop_by_hop_header,
+    is_log_ignored, is_safe_dir_path, is_safe_js_callback, is_safe_rel_path, is_safe_segment,
+    is_schedule_active, is_valid_request_id, json_path, language_variant, listen_description,
+    log_json_enabled, log_line, log_snapshot, manifest_components, matches_path_pattern,
+    new_request_id, normalize_log_pattern, normalize_rel_path, paginate_json, parse_delay_range,
+    parse_openapi_operations, parse_route_line, parse_route_options, parse_seed, parse_sse_events,
+    pick_delay, query_to_json, read_active_scenario, read_api_fallback, read_api_fallback_status,
+    read_api_token, read_auth_bypass, read_cors_origins, read_echo_endpoint, read_echo_redact,
+    read_env_substitution, read_fallback_json, read_global_delay, read_global_delay_exempt,
+    read_graphql_mappings, read_index_cache_ttl, read_index_max_depth, read_index_max_entries,
+    read_json_output, read_log_enabled, read_log_ignore_patterns, read_max_body_bytes,
+    read_max_request_bytes, read_max_upload_request_bytes, read_normalize_output,
+    read_ping_endpoint, read_rate_limit, read_readonly, read_record_enabled, read_refresh_endpoint,
+    read_refresh_expiry, read_require_json_ext, read_route_mappings, read_scenario_names,
+    read_scenario_overrides, read_sse_keepalive, read_upload_limits, read_upstream,
+    read_validate_uploads, record_assertion, scenario_file_path, select_json_pointer,
+    shadowing_builtin, store_dashboard, strip_bom_and_trailing, subscribe_fs_changes,
+    subscribe_logs, substitute_env, ui_lang, ui_text, url_encode_path, wrap_envelope, wrap_jsonp,
+    write_atomic_async, write_config_file, write_route_mappings, FakeRng, FileEntry, LogEntry,
+    RouteMapping, SCENARIOS_FOLDER,
 };
 
 // Serve JSON files under json/<subdir>/<path> with safety checks.
-pub async fn get_json(Path((subdir, path)): Path<(String, String)>) -> Response {
+pub async fn get_json(
+    Path((subdir, path)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
+    let path = normalize_rel_path(&path);
     if !is_safe_segment(&subdir) || path.is_empty() || !is_safe_rel_path(&path) {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{}/{} is not a safe path under json/", subdir, path),
+        );
+    }
+    if subdir == SCENARIOS_FOLDER {
+        return scenarios_folder_hidden();
+    }
+    let query = query.unwrap_or_default();
+    let callback = match jsonp_callback(&query) {
+        Ok(callback) => callback,
+        Err(status) => {
+            return error_response(
+                status,
+                "invalid callback",
+                "callback must be a dot-separated JavaScript identifier",
+            );
+        }
+    };
+
+    if form_value(&query, "preview").is_some_and(|v| v != "0")
+        && fs::metadata(json_path(format!("{}/{}", subdir, path)))
+            .await
+            .is_ok_and(|m| m.is_file())
+    {
+        return render_preview(format!("{}/{}", subdir, path), page_lang(&headers)).await;
+    }
+
+    if let Some(target) = path.strip_suffix("/edit")
+        && fs::metadata(json_path(format!("{}/{}", subdir, target)))
+            .await
+            .is_ok_and(|m| m.is_file())
+    {
+        return render_editor(format!("{}/{}", subdir, target), page_lang(&headers)).await;
     }
 
-    let path = base_json_dir().join(subdir).join(path);
+    let mut rel_path = format!("{}/{}", subdir, path);
+    let mut path = json_path(format!("{}/{}", subdir, path));
+    if fs::metadata(&path).await.is_ok_and(|m| m.is_dir()) {
+        let folder = rel_path.trim_end_matches('/').to_string();
+        if !is_safe_dir_path(&folder) {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid path",
+                format!("{} is not a safe folder path", folder),
+            );
+        }
+        match folder_default_file(&folder, &query, &headers).await {
+            Some("index.html") => return serve_html_file(path.join("index.html")).await,
+            Some(name) => {
+                path = path.join(name);
+                rel_path = format!("{}/{}", folder, name);
+            }
+            None => return render_folder(folder, page_lang(&headers)).await,
+        }
+    }
 
-    match fs::read(path).await {
+    match fs::read(&path).await {
         Ok(bytes) => {
+            let mut bytes = match convert_fixture(&rel_path, bytes) {
+                Ok(bytes) => bytes,
+                Err(error) => return conversion_error(error),
+            };
+            if callback.is_none() && read_normalize_output() {
+                bytes = strip_bom_and_trailing(bytes);
+            }
+            if let Some(strict) = read_env_substitution() {
+                bytes = match substitute_env(bytes, strict) {
+                    Ok(bytes) => bytes,
+                    Err(error) => return conversion_error(error),
+                };
+            }
+            let bytes = wrap_envelope(bytes, &rel_path);
+            let bytes = format_json_output(bytes, &read_json_output());
+            let (bytes, content_type) = match &callback {
+                Some(callback) => (wrap_jsonp(bytes, callback), "application/javascript"),
+                None => (bytes, "application/json"),
+            };
+            let modified = file_modified(&path).await;
+            let etag = compute_etag(&bytes, modified);
+            if let Some(response) = not_modified(&headers, &etag, modified) {
+                return response;
+            }
             let mut response = Response::new(Body::from(bytes));
+            set_validators(&mut response, &etag, modified);
             response
                 .headers_mut()
-                .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                .insert(header::CONTENT_TYPE, content_type_value(content_type));
             response.headers_mut().insert(
                 header::CACHE_CONTROL,
                 HeaderValue::from_static("no-store"),
@@ -36,29 +151,255 @@ pub async fn get_json(Path((subdir, path)): Path<(String, String)>) -> Response
             response
         }
         Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND.into_response(),
-            _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            std::io::ErrorKind::NotFound => error_response(
+                StatusCode::NOT_FOUND,
+                "not found",
+                format!("{} does not exist", rel_path),
+            ),
+            _ => error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "read failed",
+                format!("{}: {}", rel_path, err),
+            ),
         },
     }
 }
 
-// Render the main HTML dashboard.
-pub async fn index() -> Response {
+// Save a fixture from the editor form (field `content`) or a raw PUT/POST body.
+pub async fn save_json(
+    Path((subdir, path)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Response {
+    let path = normalize_rel_path(&path);
+    if !is_safe_segment(&subdir) || path.is_empty() || !is_safe_rel_path(&path) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{}/{} is not a safe path under json/", subdir, path),
+        );
+    }
+    if subdir == SCENARIOS_FOLDER {
+        return scenarios_folder_hidden();
+    }
+
+    let from_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    let bytes = if from_form {
+        let Ok(form) = std::str::from_utf8(&body) else {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid form",
+                "form body is not valid UTF-8",
+            );
+        };
+        match form_value_utf8(form, "content") {
+            Some(content) => content.into_bytes(),
+            None => return error_response(
+                StatusCode::BAD_REQUEST,
+                "missing field",
+                "form field content is required",
+            ),
+        }
+    } else {
+        body.to_vec()
+    };
+
+    if path.ends_with(".json")
+        && let Err(err) = serde_json::from_slice::<serde_json::Value>(&bytes)
+    {
+        return error_response(StatusCode::BAD_REQUEST, "invalid json", err.to_string());
+    }
+
+    let target = json_path(format!("{}/{}", subdir, path));
+    if !target.parent().is_some_and(|p| p.is_dir()) || target.is_dir() {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("the folder for {}/{} does not exist", subdir, path),
+        );
+    }
+    if let Err(err) = write_atomic_async(target, bytes).await {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "write failed",
+            format!("{}/{}: {}", subdir, path, err),
+        );
+    }
+
+    if from_form {
+        let rel_path = format!("{}/{}", subdir, path);
+        let folder = rel_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(&subdir);
+        Redirect::to(&format!("/json/{}", folder)).into_response()
+    } else {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+// Render the in-browser editor for a fixture file.
+async fn render_editor(rel_path: String, lang: &'static str) -> Response {
+    let t = |key| ui_text(lang, key);
+    let contents = match fs::read(json_path(&rel_path)).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(_) => return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", rel_path),
+        ),
+    };
+    let back = rel_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(&rel_path);
+
+    let mut body = format!("<!doctype html><html lang=\"{}\">", lang);
+    body.push_str("<head><meta charset=\"utf-8\"><title>");
+    body.push_str(t("edit_file"));
+    body.push_str(
+        "</title><style>
+        :root{--bg:#0b0f1a;--card:#12192a;--accent:#ffb703;--text:#e5ecf4;--muted:#93a3b8;}
+        *{box-sizing:border-box}body{margin:0;font-family:\"Space Grotesk\",system-ui,-apple-system,sans-serif;color:var(--text);
+        background:radial-gradient(1200px 600px at 10% -10%, #1d2b4a 0%, transparent 60%),linear-gradient(180deg,#0b0f1a 0%,#0d1222 100%);}
+        a{color:var(--accent);text-decoration:none}a:hover{text-decoration:underline}
+        header{padding:32px 24px 12px;max-width:900px;margin:0 auto}
+        h1{margin:0;font-size:28px}
+        .wrap{max-width:900px;margin:0 auto;padding:0 24px 40px}
+        .card{background:var(--card);border:1px solid #1e2842;border-radius:14px;padding:16px;margin-bottom:16px}
+        p{color:var(--muted)}
+        textarea{width:100%;min-height:60vh;padding:12px;border-radius:10px;border:1px solid #1f2a44;background:#0d1425;color:var(--text);font-family:ui-monospace,monospace;font-size:13px}
+        button{margin-top:10px;background:var(--accent);border:none;color:#111;padding:10px 16px;border-radius:10px;font-weight:600;cursor:pointer}
+        </style></head><body><header>",
+    );
+    body.push_str("<a href=\"/json/");
+    body.push_str(&url_encode_path(back));
+    body.push_str(&format!("\">{}</a><h1>{} <code>", t("back_to_folder"), t("edit")));
+    body.push_str(&html_escape(&rel_path));
+    body.push_str("</code></h1></header><div class=\"wrap\">");
+    body.push_str(&readonly_notice(lang));
+    body.push_str("<div class=\"card\"><form method=\"post\" action=\"/json/");
+    body.push_str(&url_encode_path(&rel_path));
+    body.push_str("\"><textarea name=\"content\" spellcheck=\"false\">");
+    body.push_str(&html_escape(&contents));
+    body.push_str(&format!("</textarea><p>{}</p>", t("validated")));
+    body.push_str(&format!(
+        "<button type=\"submit\">{}</button></form></div></div></body></html>",
+        t("save")
+    ));
+
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Serve the fixture index (files and subdirectories) as JSON.
+pub async fn json_index() -> Response {
+    let base_dir = base_json_dir();
+    let (max_depth, max_entries) = (read_index_max_depth(), read_index_max_entries());
+    let (entries, subdirs, truncated) =
+        tokio::task::spawn_blocking(move || collect_json_index(base_dir, max_depth, max_entries))
+            .await
+            .unwrap_or_default();
+    let files: Vec<serde_json::Value> = entries.iter().map(FileEntry::to_json).collect();
+    let body = serde_json::json!({ "files": files, "subdirs": subdirs, "truncated": truncated })
+        .to_string();
+
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Render the main HTML dashboard, or the fixture index as JSON when asked via Accept.
+pub async fn index(RawQuery(query): RawQuery, headers: HeaderMap) -> Response {
+    let wants_json = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/json") && !v.contains("text/html"));
+    if wants_json {
+        return json_index().await;
+    }
+
+    let lang = page_lang(&headers);
+    let fresh = form_value(&query.unwrap_or_default(), "fresh").is_some_and(|v| v != "0");
+    let ttl = read_index_cache_ttl();
+    let page = match cached_dashboard(lang, ttl).filter(|_| !fresh) {
+        Some(page) => page,
+        None => {
+            let generation = fs_generation();
+            let page = render_dashboard(lang).await;
+            if !ttl.is_zero() {
+                store_dashboard(lang, generation, page.clone());
+            }
+            page
+        }
+    };
+    let body = page.replacen(LOG_MARKER, &log_lines_html(), 1);
+
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Placeholder in the cached dashboard where the live request log is filled in.
+const LOG_MARKER: &str = "<!--request-log-->";
+
+// Render the request log lines for the dashboard's log panel.
+fn log_lines_html() -> String {
+    let mut html = String::new();
+    for entry in log_snapshot() {
+        html.push_str("<div class=\"log-line\" data-method=\"");
+        html.push_str(&html_escape(entry.method.as_deref().unwrap_or_default()));
+        html.push_str("\" data-status=\"");
+        if let Some(status) = entry.status {
+            html.push_str(&status.to_string());
+        }
+        html.push_str("\">");
+        html.push_str(&html_escape(&entry.to_line()));
+        html.push_str("</div>");
+    }
+    html
+}
+
+// Build the dashboard HTML, leaving LOG_MARKER where the request log goes.
+async fn render_dashboard(lang: &'static str) -> String {
     let base_dir = base_json_dir();
+    let t = |key| ui_text(lang, key);
 
     let refresh_endpoint = read_refresh_endpoint();
     let ping_endpoint = read_ping_endpoint();
     let route_mappings = read_route_mappings();
+    let scenario_names = read_scenario_names();
+    let active_scenario = read_active_scenario();
+    let scenario_overrides = read_scenario_overrides();
     let log_patterns = read_log_ignore_patterns();
     let log_enabled = read_log_enabled();
-    let log_snapshot = log_snapshot();
-    let (entries, subdirs) =
-        tokio::task::spawn_blocking(move || collect_json_index(base_dir))
+    let fs_changes = fs_changes_snapshot();
+    let (max_depth, max_entries) = (read_index_max_depth(), read_index_max_entries());
+    let dangling = dangling_route_mappings(&route_mappings, &base_dir);
+    let (entries, subdirs, truncated) =
+        tokio::task::spawn_blocking(move || collect_json_index(base_dir, max_depth, max_entries))
             .await
             .unwrap_or_default();
 
-    let mut body = String::from(
-        "<!doctype html><html><head><meta charset=\"utf-8\"><title>JSON endpoints</title><style>
+    let mut body = format!("<!doctype html><html lang=\"{}\">", lang);
+    body.push_str(
+        "<head><meta charset=\"utf-8\"><title>JSON endpoints</title><style>
         :root{--bg:#0b0f1a;--card:#12192a;--accent:#ffb703;--accent2:#219ebc;--text:#e5ecf4;--muted:#93a3b8;}
         *{box-sizing:border-box}body{margin:0;font-family:\"Space Grotesk\",system-ui,-apple-system,sans-serif;color:var(--text);
         background:radial-gradient(1200px 600px at 10% -10%, #1d2b4a 0%, transparent 60%),linear-gradient(180deg,#0b0f1a 0%,#0d1222 100%);}
@@ -81,192 +422,391 @@ pub async fn index() -> Response {
         .log{background:#0d1425;border:1px solid #1f2a44;border-radius:12px;padding:10px;max-height:220px;overflow:auto;font-family:ui-monospace,SFMono-Regular,Menlo,Monaco,Consolas,\"Liberation Mono\",monospace;font-size:12px}
         .log-line{padding:4px 0;border-bottom:1px dashed #1f2a44}
         .log-line:last-child{border-bottom:none}
+        .log-line.status-2xx{color:#06d6a0}.log-line.status-4xx{color:#ffb703}.log-line.status-5xx{color:#ef476f}
+        .log-filters{display:flex;gap:8px;margin-bottom:8px;align-items:center}
+        .log-filters select{width:auto}
         .tabs{max-width:1000px;margin:0 auto;padding:0 24px 8px;display:flex;gap:8px;flex-wrap:wrap}
         .tab-btn{border:1px solid #1f2a44;background:#0d1425;color:var(--text);padding:8px 14px;border-radius:999px;cursor:pointer}
         .tab-btn.active{background:var(--accent);color:#111;border-color:transparent}
         .tab-panel{display:none}
         .tab-panel.active{display:block}
-        </style></head><body><header><span class=\"pill\">API stub</span><h1>JSON endpoints</h1>
-        <p>Questa app espone automaticamente i file presenti in <code>json/</code> come endpoint HTTP. Ogni file diventa raggiungibile con <code>/json/&lt;sottocartella&gt;/&lt;file&gt;</code>. Le risposte vengono lette dal disco a ogni richiesta, quindi gli aggiornamenti sono immediati.</p>
-        <p class=\"muted\">Autore: Alessandro Iannacone - <a href=\"https://iannaconealessandro.it\">iannaconealessandro.it</a></p>
-        </header>",
+        form.inline{display:inline}form.inline button{margin:0 0 0 6px;padding:2px 8px;font-size:12px;background:#ef476f;color:#fff;border:none;border-radius:8px;cursor:pointer}
+        .badge-warn{display:inline-block;margin-left:6px;padding:2px 8px;border-radius:999px;background:rgba(239,71,111,0.18);color:#ef476f;font-size:12px}
+        </style></head><body><header><span class=\"pill\">API stub</span><h1>JSON endpoints</h1>",
     );
+    body.push_str(&format!("<p>{}</p>", t("intro")));
+    body.push_str(&format!(
+        "<p class=\"muted\">{}: Alessandro Iannacone - <a href=\"https://iannaconealessandro.it\">iannaconealessandro.it</a></p></header>",
+        t("author")
+    ));
+    body.push_str(&readonly_notice(lang));
 
     body.push_str("<div class=\"tabs\">");
-    body.push_str("<button class=\"tab-btn active\" data-tab=\"overview\">Panoramica</button>");
-    body.push_str("<button class=\"tab-btn\" data-tab=\"routing\">Routing API</button>");
-    body.push_str("<button class=\"tab-btn\" data-tab=\"settings\">Impostazioni</button>");
+    for (id, key) in [
+        ("overview", "tab_overview"),
+        ("routing", "tab_routing"),
+        ("settings", "tab_settings"),
+    ] {
+        body.push_str(&format!(
+            "<button class=\"tab-btn{}\" data-tab=\"{}\">{}</button>",
+            if id == "overview" { " active" } else { "" },
+            id,
+            t(key)
+        ));
+    }
     body.push_str("</div>");
 
     body.push_str("<div id=\"overview\" class=\"tab-panel active\">");
-    body.push_str("<section class=\"section\"><div class=\"card\"><h2>Log richieste (live)</h2>");
+    body.push_str(&format!(
+        "<section class=\"section\"><div class=\"card\"><h2>{}</h2>",
+        t("request_log")
+    ));
+    body.push_str("<div class=\"log-filters\">");
+    body.push_str(&format!(
+        "<select id=\"log-method\"><option value=\"\">{}</option>",
+        t("all_methods")
+    ));
+    for method in ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"] {
+        body.push_str("<option>");
+        body.push_str(method);
+        body.push_str("</option>");
+    }
+    body.push_str("</select>");
+    body.push_str(&format!(
+        "<select id=\"log-status\"><option value=\"\">{}</option>",
+        t("all_statuses")
+    ));
+    for class in ["2", "3", "4", "5"] {
+        body.push_str("<option value=\"");
+        body.push_str(class);
+        body.push_str("\">");
+        body.push_str(class);
+        body.push_str("xx</option>");
+    }
+    body.push_str("</select>");
+    body.push_str(&format!(
+        "<a href=\"/events/export\">{0} .txt</a> <a href=\"/events/export?format=json\">{0} .json</a></div>",
+        t("export")
+    ));
     body.push_str("<div id=\"log\" class=\"log\">");
-    for line in log_snapshot {
+    body.push_str(LOG_MARKER);
+    body.push_str("</div></div></section>");
+
+    body.push_str(&format!(
+        "<section class=\"section\"><div class=\"card\"><h2>{}</h2>",
+        t("fs_log")
+    ));
+    body.push_str("<div id=\"fs-log\" class=\"log\">");
+    for change in &fs_changes {
         body.push_str("<div class=\"log-line\">");
-        body.push_str(&html_escape(&line));
+        body.push_str(&html_escape(&format!("{} {} {}", change.timestamp, change.kind, change.path)));
         body.push_str("</div>");
     }
+    if fs_changes.is_empty() {
+        body.push_str(&format!("<div class=\"log-line muted\">{}</div>", t("no_changes")));
+    }
     body.push_str("</div></div></section>");
 
-    body.push_str("<section class=\"section\"><div class=\"card\"><h2>Endpoint attivi</h2>");
+    body.push_str(&format!(
+        "<section class=\"section\"><div class=\"card\"><h2>{}</h2>",
+        t("active_endpoints")
+    ));
     body.push_str("<p class=\"muted\">Refresh: <code>");
-    body.push_str(&refresh_endpoint);
+    body.push_str(&html_escape(&refresh_endpoint));
     body.push_str("</code></p>");
     body.push_str("<p class=\"muted\">Ping: <code>");
-    body.push_str(&ping_endpoint);
+    body.push_str(&html_escape(&ping_endpoint));
     body.push_str("</code></p>");
-    body.push_str("<div class=\"tag\">Mappature API</div><ul>");
+    body.push_str(&format!("<div class=\"tag\">{}</div><ul>", t("api_mappings")));
     for mapping in &route_mappings {
         body.push_str("<li><span class=\"pill\">");
-        body.push_str(&mapping.method);
+        body.push_str(&html_escape(&mapping.method));
         body.push_str("</span> <code>");
-        body.push_str(&mapping.path);
+        body.push_str(&html_escape(&mapping.path));
         body.push_str("</code> → <a href=\"/json/");
-        body.push_str(&mapping.file);
+        body.push_str(&url_encode_path(&mapping.file));
         body.push_str("\">");
-        body.push_str(&mapping.file);
-        body.push_str("</a></li>");
+        body.push_str(&html_escape(&mapping.file));
+        body.push_str("</a>");
+        if let Some((_, missing)) = dangling
+            .iter()
+            .find(|(m, _)| m.method == mapping.method && m.path == mapping.path)
+        {
+            body.push_str(" <span class=\"badge-warn\" title=\"");
+            body.push_str(&html_escape(&missing.join(", ")));
+            body.push_str("\">");
+            body.push_str(t("missing_file"));
+            body.push_str("</span>");
+        }
+        if let Some(builtin) = shadowing_builtin(&mapping.method, &mapping.path) {
+            body.push_str(&format!(
+                " <span class=\"badge-warn\" title=\"{}\">{}</span>",
+                t("shadow_title").replace("{}", builtin),
+                t("shadowed_by").replace("{}", builtin)
+            ));
+        }
+        body.push_str("<form class=\"inline\" method=\"post\" action=\"/config/route-delete\"><input type=\"hidden\" name=\"method\" value=\"");
+        body.push_str(&html_escape(&mapping.method));
+        body.push_str("\"><input type=\"hidden\" name=\"path\" value=\"");
+        body.push_str(&html_escape(&mapping.path));
+        body.push_str(&format!(
+            "\"><button type=\"submit\">{}</button></form></li>",
+            t("delete_lower")
+        ));
     }
     if route_mappings.is_empty() {
-        body.push_str("<li class=\"muted\">Nessuna mappatura configurata</li>");
+        body.push_str(&format!("<li class=\"muted\">{}</li>", t("no_mappings")));
     }
     body.push_str("</ul></div></section>");
 
     body.push_str("<section class=\"grid\">");
-    body.push_str("<div class=\"card\"><div class=\"tag\">Sottocartelle</div><ul>");
+    body.push_str(&format!("<div class=\"card\"><div class=\"tag\">{}</div><ul>", t("subdirs")));
     for subdir in &subdirs {
-        body.push_str("<li><a href=\"/json/");
-        body.push_str(&subdir);
+        let depth = subdir.matches('/').count();
+        let name = subdir.rsplit('/').next().unwrap_or(subdir);
+        body.push_str("<li style=\"padding-left:");
+        body.push_str(&(depth * 18).to_string());
+        body.push_str("px\"><a href=\"/json/");
+        body.push_str(&url_encode_path(subdir));
         body.push_str("\">");
-        body.push_str(&subdir);
+        body.push_str(&html_escape(name));
         body.push_str("</a> <span class=\"muted\">/json/");
-        body.push_str(&subdir);
+        body.push_str(&html_escape(subdir));
         body.push_str("</span></li>");
     }
     body.push_str("</ul></div>");
 
-    body.push_str("<div class=\"card\"><div class=\"tag\">File disponibili</div><ul>");
-    for (path, url) in &entries {
-        body.push_str("<li><a href=\"");
-        body.push_str(&url);
-        body.push_str("\">");
-        body.push_str(&path);
-        body.push_str("</a></li>");
+    body.push_str(&format!("<div class=\"card\"><div class=\"tag\">{}</div>", t("files")));
+    body.push_str(&file_filter(lang));
+    body.push_str("<ul class=\"file-list\">");
+    for entry in &entries {
+        body.push_str("<li>");
+        body.push_str(&file_link_html(entry));
+        body.push_str(&file_meta_html(entry));
+        body.push_str("</li>");
     }
-    body.push_str("</ul></div>");
+    body.push_str("</ul>");
+    if truncated {
+        let note = t("truncated")
+            .replace("{entries}", &max_entries.to_string())
+            .replace("{depth}", &max_depth.to_string());
+        body.push_str(&format!("<p class=\"muted\">{}</p>", note));
+    }
+    body.push_str("</div>");
     body.push_str("</section></div>");
 
     body.push_str("<div id=\"routing\" class=\"tab-panel\">");
-    body.push_str("<section class=\"section\"><div class=\"card\"><h2>Routing API</h2>");
-    body.push_str("<p class=\"muted\">Associa un endpoint <code>/api/...</code> a un file JSON in <code>json/</code>.</p>");
+    body.push_str(&format!(
+        "<section class=\"section\"><div class=\"card\"><h2>{}</h2>",
+        t("tab_routing")
+    ));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("routing_intro")));
+    let shadowed = route_mappings
+        .iter()
+        .filter(|m| shadowing_builtin(&m.method, &m.path).is_some())
+        .count();
+    if shadowed > 0 {
+        body.push_str(&format!(
+            "<p><span class=\"badge-warn\">{}</span> <span class=\"muted\">{}</span></p>",
+            t("shadowed_count").replace("{}", &shadowed.to_string()),
+            t("shadowed_hint")
+        ));
+    }
     body.push_str("<form method=\"post\" action=\"/config/route-mapping\">");
-    body.push_str("<label class=\"muted\">Metodo</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("method")));
     body.push_str("<select name=\"method\"><option>GET</option><option>POST</option></select>");
     body.push_str("<label class=\"muted\">Path</label>");
     body.push_str("<input type=\"text\" name=\"path\" placeholder=\"/api/v1/ipv4/get/all\" required>");
-    body.push_str("<label class=\"muted\">File (relativo a json/)</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("route_file")));
     body.push_str("<input type=\"text\" name=\"file\" list=\"file-options\" placeholder=\"ipv4/file.json\" required>");
-    body.push_str("<button type=\"submit\">Associa</button></form>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("route_options")));
+    body.push_str("<input type=\"text\" name=\"options\" placeholder=\"schedule=09:00-18:00 tz=Europe/Rome schedule_file=shop/open.json\">");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("map")));
     body.push_str("<datalist id=\"file-options\">");
-    for (path, _url) in &entries {
+    for entry in &entries {
         body.push_str("<option value=\"");
-        body.push_str(path);
+        body.push_str(&html_escape(&entry.path));
         body.push_str("\"></option>");
     }
     body.push_str("</datalist>");
 
-    body.push_str("<div class=\"tag\">Associazioni attive</div><ul>");
+    body.push_str("<form method=\"post\" action=\"/config/routes-bulk\">");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("bulk_label")));
+    body.push_str("<textarea name=\"routes\" rows=\"4\" placeholder=\"GET /api/v1/users users/all.json mode=paginate\" required></textarea>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("bulk_submit")));
+
+    body.push_str("<form method=\"post\" action=\"/config/import-openapi\" enctype=\"multipart/form-data\">");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("openapi_label")));
+    body.push_str("<input type=\"file\" name=\"spec\" accept=\".yaml,.yml,.json\" required>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("import")));
+
+    body.push_str(&format!("<div class=\"tag\">{}</div><ul>", t("active_mappings")));
     for mapping in &route_mappings {
         body.push_str("<li><span class=\"pill\">");
-        body.push_str(&mapping.method);
+        body.push_str(&html_escape(&mapping.method));
         body.push_str("</span> <code>");
-        body.push_str(&mapping.path);
+        body.push_str(&html_escape(&mapping.path));
         body.push_str("</code> → <a href=\"/json/");
-        body.push_str(&mapping.file);
+        body.push_str(&url_encode_path(&mapping.file));
         body.push_str("\">");
-        body.push_str(&mapping.file);
-        body.push_str("</a></li>");
+        body.push_str(&html_escape(&mapping.file));
+        body.push_str("</a>");
+        if let Some((_, missing)) = dangling
+            .iter()
+            .find(|(m, _)| m.method == mapping.method && m.path == mapping.path)
+        {
+            body.push_str(" <span class=\"badge-warn\" title=\"");
+            body.push_str(&html_escape(&missing.join(", ")));
+            body.push_str("\">");
+            body.push_str(t("missing_file"));
+            body.push_str("</span>");
+        }
+        if let Some(builtin) = shadowing_builtin(&mapping.method, &mapping.path) {
+            body.push_str(&format!(
+                " <span class=\"badge-warn\" title=\"{}\">{}</span>",
+                t("shadow_title").replace("{}", builtin),
+                t("shadowed_by").replace("{}", builtin)
+            ));
+        }
+        body.push_str("<form class=\"inline\" method=\"post\" action=\"/config/route-delete\"><input type=\"hidden\" name=\"method\" value=\"");
+        body.push_str(&html_escape(&mapping.method));
+        body.push_str("\"><input type=\"hidden\" name=\"path\" value=\"");
+        body.push_str(&html_escape(&mapping.path));
+        body.push_str(&format!(
+            "\"><button type=\"submit\">{}</button></form></li>",
+            t("delete_lower")
+        ));
     }
     if route_mappings.is_empty() {
-        body.push_str("<li class=\"muted\">Nessuna associazione configurata</li>");
+        body.push_str(&format!("<li class=\"muted\">{}</li>", t("no_routes")));
+    }
+    body.push_str("</ul></div>");
+
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("scenarios")));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("scenarios_intro")));
+    body.push_str(&format!("<p class=\"muted\">{} <code>", t("active_scenario")));
+    body.push_str(&html_escape(active_scenario.as_deref().unwrap_or(t("none"))));
+    body.push_str("</code></p>");
+    body.push_str("<form method=\"post\" action=\"/config/scenario\">");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("activate_scenario")));
+    body.push_str(&format!(
+        "<select name=\"name\"><option value=\"\">{}</option>",
+        t("no_scenario")
+    ));
+    for name in &scenario_names {
+        body.push_str("<option value=\"");
+        body.push_str(&html_escape(name));
+        body.push('"');
+        if active_scenario.as_deref() == Some(name.as_str()) {
+            body.push_str(" selected");
+        }
+        body.push('>');
+        body.push_str(&html_escape(name));
+        body.push_str("</option>");
+    }
+    body.push_str("</select>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("apply")));
+    body.push_str(&format!("<div class=\"tag\">{}</div><ul>", t("overrides")));
+    for o in &scenario_overrides {
+        body.push_str("<li><span class=\"pill\">");
+        body.push_str(&html_escape(&o.scenario));
+        body.push_str("</span> <span class=\"pill\">");
+        body.push_str(&html_escape(&o.method));
+        body.push_str("</span> <code>");
+        body.push_str(&html_escape(&o.path));
+        body.push_str("</code> → <a href=\"/json/");
+        body.push_str(&url_encode_path(&o.file));
+        body.push_str("\">");
+        body.push_str(&html_escape(&o.file));
+        body.push_str("</a></li>");
+    }
+    if scenario_overrides.is_empty() {
+        body.push_str(&format!("<li class=\"muted\">{}</li>", t("no_scenarios")));
     }
     body.push_str("</ul></div></section></div>");
 
     body.push_str("<div id=\"settings\" class=\"tab-panel\">");
     body.push_str("<section class=\"section\">");
-    body.push_str("<div class=\"card\"><h2>Autenticazione</h2>");
-    body.push_str("<p class=\"muted\">Configura l'endpoint di refresh token e usa la risposta JSON salvata su disco.</p>");
-    body.push_str("<p class=\"muted\">Endpoint attuale: <code>");
-    body.push_str(&refresh_endpoint);
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("auth")));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("auth_intro")));
+    body.push_str(&format!("<p class=\"muted\">{} <code>", t("current_endpoint")));
+    body.push_str(&html_escape(&refresh_endpoint));
     body.push_str("</code></p>");
     body.push_str("<form method=\"post\" action=\"/config/refresh-endpoint\">");
-    body.push_str("<label class=\"muted\">Imposta un endpoint sotto <code>/api/</code></label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("set_endpoint")));
     body.push_str("<input type=\"text\" name=\"path\" value=\"");
-    body.push_str(&refresh_endpoint);
+    body.push_str(&html_escape(&refresh_endpoint));
     body.push_str("\" required>");
-    body.push_str("<button type=\"submit\">Aggiorna</button></form></div>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form></div>", t("update")));
 
     body.push_str("<div class=\"card\"><h2>Ping API</h2>");
-    body.push_str("<p class=\"muted\">Endpoint di check connessione che ritorna uno stato JSON.</p>");
-    body.push_str("<p class=\"muted\">Endpoint attuale: <code>");
-    body.push_str(&ping_endpoint);
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("ping_intro")));
+    body.push_str(&format!("<p class=\"muted\">{} <code>", t("current_endpoint")));
+    body.push_str(&html_escape(&ping_endpoint));
     body.push_str("</code></p>");
     body.push_str("<form method=\"post\" action=\"/config/ping-endpoint\">");
-    body.push_str("<label class=\"muted\">Imposta un endpoint sotto <code>/api/</code></label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("set_endpoint")));
     body.push_str("<input type=\"text\" name=\"path\" value=\"");
-    body.push_str(&ping_endpoint);
+    body.push_str(&html_escape(&ping_endpoint));
     body.push_str("\" required>");
-    body.push_str("<button type=\"submit\">Aggiorna</button></form></div>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form></div>", t("update")));
 
-    body.push_str("<div class=\"card\"><h2>Gestione cartelle</h2>");
-    body.push_str("<p class=\"muted\">Crea, rinomina o elimina sottocartelle sotto <code>json/</code>.</p>");
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("folders")));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("folders_intro")));
     body.push_str("<form method=\"post\" action=\"/json/create\">");
-    body.push_str("<label class=\"muted\">Nome sottocartella</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("folder_name")));
     body.push_str("<input type=\"text\" name=\"name\" required>");
-    body.push_str("<button type=\"submit\">Crea</button></form>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("create")));
 
     body.push_str("<form method=\"post\" action=\"/json/rename\">");
-    body.push_str("<label class=\"muted\">Rinomina cartella</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("rename_folder")));
     body.push_str("<select name=\"from\">");
-    for subdir in &subdirs {
+    for subdir in subdirs.iter().filter(|s| !s.contains('/')) {
         body.push_str("<option value=\"");
-        body.push_str(subdir);
+        body.push_str(&html_escape(subdir));
         body.push_str("\">");
-        body.push_str(subdir);
+        body.push_str(&html_escape(subdir));
         body.push_str("</option>");
     }
     body.push_str("</select>");
-    body.push_str("<input type=\"text\" name=\"to\" placeholder=\"nuovo_nome\" required>");
-    body.push_str("<button type=\"submit\">Rinomina</button></form>");
+    body.push_str(&format!(
+        "<input type=\"text\" name=\"to\" placeholder=\"{}\" required>",
+        t("new_name")
+    ));
+    body.push_str(&format!("<button type=\"submit\">{}</button></form>", t("rename")));
 
     body.push_str("<form method=\"post\" action=\"/json/delete\">");
-    body.push_str("<label class=\"muted\">Elimina cartella</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("delete_folder")));
     body.push_str("<select name=\"name\">");
-    for subdir in &subdirs {
+    for subdir in subdirs.iter().filter(|s| !s.contains('/')) {
         body.push_str("<option value=\"");
-        body.push_str(subdir);
+        body.push_str(&html_escape(subdir));
         body.push_str("\">");
-        body.push_str(subdir);
+        body.push_str(&html_escape(subdir));
         body.push_str("</option>");
     }
     body.push_str("</select>");
-    body.push_str("<button type=\"submit\">Elimina</button></form></div>");
-
-    body.push_str("<div class=\"card\"><h2>Filtri log</h2>");
-    body.push_str("<p class=\"muted\">Inserisci uno per riga. Supporta match esatto o prefisso con <code>/*</code> (es. <code>/json/*</code>).</p>");
+    body.push_str(&format!(
+        "<input type=\"text\" name=\"confirm\" placeholder=\"{}\" required>",
+        t("confirm_name")
+    ));
+    body.push_str(&format!("<button type=\"submit\">{}</button></form></div>", t("delete")));
+
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("log_filters")));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("log_filters_intro")));
     body.push_str("<form method=\"post\" action=\"/config/log-ignore\">");
-    body.push_str("<label class=\"muted\">Path da ignorare</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("ignored_paths")));
     body.push_str("<textarea name=\"patterns\" rows=\"4\" required>");
     if !log_patterns.is_empty() {
         body.push_str(&html_escape(&log_patterns.join("\n")));
     }
     body.push_str("</textarea>");
-    body.push_str("<button type=\"submit\">Aggiorna</button></form></div>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form></div>", t("update")));
 
-    body.push_str("<div class=\"card\"><h2>Log globale</h2>");
-    body.push_str("<p class=\"muted\">Abilita o disabilita completamente i log di richieste e risposte.</p>");
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("global_log")));
+    body.push_str(&format!("<p class=\"muted\">{}</p>", t("global_log_intro")));
     body.push_str("<form method=\"post\" action=\"/config/log-toggle\">");
-    body.push_str("<label class=\"muted\">Stato log</label>");
+    body.push_str(&format!("<label class=\"muted\">{}</label>", t("log_state")));
     body.push_str("<select name=\"enabled\">");
     body.push_str("<option value=\"on\"");
     if log_enabled {
@@ -279,17 +819,49 @@ pub async fn index() -> Response {
     }
     body.push_str(">OFF</option>");
     body.push_str("</select>");
-    body.push_str("<button type=\"submit\">Salva</button></form></div>");
+    body.push_str(&format!("<button type=\"submit\">{}</button></form></div>", t("save")));
     body.push_str("</section></div>");
 
     body.push_str("<script>
     (function(){
         const logEl = document.getElementById('log');
-        const es = new EventSource('/events');
+        const methodEl = document.getElementById('log-method');
+        const statusEl = document.getElementById('log-status');
+        const matches = (line) => {
+            const method = methodEl.value;
+            const status = statusEl.value;
+            if (method && line.dataset.method !== method) {
+                return false;
+            }
+            if (status && !line.dataset.status.startsWith(status)) {
+                return false;
+            }
+            return true;
+        };
+        const applyFilter = () => {
+            Array.from(logEl.children).forEach(line => {
+                line.style.display = matches(line) ? '' : 'none';
+            });
+        };
+        const colorize = (line) => {
+            const cls = ({'2': 'status-2xx', '4': 'status-4xx', '5': 'status-5xx'})[line.dataset.status.charAt(0)];
+            if (cls) {
+                line.classList.add(cls);
+            }
+        };
+        Array.from(logEl.children).forEach(colorize);
+        methodEl.addEventListener('change', applyFilter);
+        statusEl.addEventListener('change', applyFilter);
+        const es = new EventSource('/events?format=json');
         es.onmessage = (e) => {
+            const entry = JSON.parse(e.data);
             const line = document.createElement('div');
             line.className = 'log-line';
-            line.textContent = e.data;
+            line.dataset.method = entry.method || '';
+            line.dataset.status = entry.status ? String(entry.status) : '';
+            line.textContent = entry.line;
+            colorize(line);
+            line.style.display = matches(line) ? '' : 'none';
             logEl.appendChild(line);
             while (logEl.children.length > 200) {
                 logEl.removeChild(logEl.firstChild);
@@ -297,6 +869,22 @@ pub async fn index() -> Response {
             logEl.scrollTop = logEl.scrollHeight;
         };
 
+        const fsEl = document.getElementById('fs-log');
+        const fsEs = new EventSource('/events/fs');
+        fsEs.onmessage = (e) => {
+            const empty = fsEl.querySelector('.muted');
+            if (empty) {
+                fsEl.removeChild(empty);
+            }
+            const line = document.createElement('div');
+            line.className = 'log-line';
+            line.textContent = e.data;
+            fsEl.insertBefore(line, fsEl.firstChild);
+            while (fsEl.children.length > 100) {
+                fsEl.removeChild(fsEl.lastChild);
+            }
+        };
+
         const buttons = document.querySelectorAll('.tab-btn');
         const panels = document.querySelectorAll('.tab-panel');
         const activate = (id) => {
@@ -308,6 +896,57 @@ pub async fn index() -> Response {
         });
     })();
     </script></body></html>");
+    body
+}
+
+// Render a read-only page with the file pretty-printed and syntax-highlighted.
+async fn render_preview(rel_path: String, lang: &'static str) -> Response {
+    let t = |key| ui_text(lang, key);
+    let bytes = match fs::read(json_path(&rel_path)).await {
+        Ok(bytes) => bytes,
+        Err(_) => return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", rel_path),
+        ),
+    };
+    let code = match serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+    {
+        Some(pretty) => highlight_json(&pretty),
+        None => html_escape(&String::from_utf8_lossy(&bytes)),
+    };
+    let back = rel_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(&rel_path);
+
+    let mut body = format!("<!doctype html><html lang=\"{}\">", lang);
+    body.push_str("<head><meta charset=\"utf-8\"><title>");
+    body.push_str(t("preview_file"));
+    body.push_str(
+        "</title><style>
+        :root{--bg:#0b0f1a;--card:#12192a;--accent:#ffb703;--text:#e5ecf4;--muted:#93a3b8;}
+        *{box-sizing:border-box}body{margin:0;font-family:\"Space Grotesk\",system-ui,-apple-system,sans-serif;color:var(--text);
+        background:radial-gradient(1200px 600px at 10% -10%, #1d2b4a 0%, transparent 60%),linear-gradient(180deg,#0b0f1a 0%,#0d1222 100%);}
+        a{color:var(--accent);text-decoration:none}a:hover{text-decoration:underline}
+        header{padding:32px 24px 12px;max-width:900px;margin:0 auto}
+        h1{margin:0;font-size:28px}
+        .wrap{max-width:900px;margin:0 auto;padding:0 24px 40px}
+        .card{background:var(--card);border:1px solid #1e2842;border-radius:14px;padding:16px;margin-bottom:16px}
+        pre{margin:0;overflow:auto;font-family:ui-monospace,monospace;font-size:13px}
+        .k{color:#8ecae6}.s{color:#06d6a0}.n{color:#ffb703}.b{color:#ef476f}
+        </style></head><body><header>",
+    );
+    body.push_str("<a href=\"/json/");
+    body.push_str(&url_encode_path(back));
+    body.push_str(&format!("\">{}</a><h1>{} <code>", t("back_to_folder"), t("preview")));
+    body.push_str(&html_escape(&rel_path));
+    body.push_str("</code></h1><p><a href=\"/json/");
+    body.push_str(&url_encode_path(&rel_path));
+    body.push_str("\">");
+    body.push_str(t("original_file"));
+    body.push_str("</a></p></header><div class=\"wrap\"><div class=\"card\"><pre>");
+    body.push_str(&code);
+    body.push_str("</pre></div></div></body></html>");
 
     let mut response = Response::new(Body::from(body));
     response
@@ -320,20 +959,125 @@ pub async fn index() -> Response {
     response
 }
 
+// Render a file link showing its full json/-relative path, folder part muted.
+fn file_link_html(entry: &FileEntry) -> String {
+    let (folder, name) = match entry.path.rsplit_once('/') {
+        Some((folder, name)) => (format!("{}/", folder), name),
+        None => (String::new(), entry.path.as_str()),
+    };
+    format!(
+        "<a href=\"{}\" title=\"{}\"><span class=\"muted\">{}</span>{}</a>",
+        entry.url,
+        html_escape(&entry.path),
+        html_escape(&folder),
+        html_escape(name)
+    )
+}
+
+// Render a file's size and modification time next to its link.
+fn file_meta_html(entry: &FileEntry) -> String {
+    format!(
+        " <span class=\"muted\">{} {}</span>",
+        entry.size_label(),
+        entry.modified_label()
+    )
+}
+
 // Render per-subdirectory page with file list and upload form.
-pub async fn subdir_index(Path(subdir): Path<String>) -> Response {
+pub async fn subdir_index(
+    Path(subdir): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
     if !is_safe_segment(&subdir) {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe folder name", subdir),
+        );
+    }
+    if subdir == SCENARIOS_FOLDER {
+        return scenarios_folder_hidden();
     }
 
-    let base_dir = base_json_dir().join(&subdir);
-    let subdir_clone = subdir.clone();
-    let entries = tokio::task::spawn_blocking(move || collect_subdir_entries(base_dir, subdir_clone))
-        .await
+    let query = query.unwrap_or_default();
+    match folder_default_file(&subdir, &query, &headers).await {
+        Some("index.html") => {
+            serve_html_file(json_path(&subdir).join("index.html")).await
+        }
+        Some(name) => {
+            get_json(Path((subdir, name.to_string())), RawQuery(Some(query)), headers).await
+        }
+        None => render_folder(subdir, page_lang(&headers)).await,
+    }
+}
+
+// Pick a folder's index.html or index.json when Accept prefers it, unless ?browse=1.
+async fn folder_default_file(folder: &str, query: &str, headers: &HeaderMap) -> Option<&'static str> {
+    if form_value(query, "browse").is_some_and(|v| v != "0") {
+        return None;
+    }
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
         .unwrap_or_default();
+    let name = if accept.contains("text/html") {
+        "index.html"
+    } else if accept.contains("application/json") {
+        "index.json"
+    } else {
+        return None;
+    };
+    fs::metadata(json_path(folder).join(name))
+        .await
+        .is_ok_and(|m| m.is_file())
+        .then_some(name)
+}
+
+// Serve an HTML file from disk as-is.
+async fn serve_html_file(path: std::path::PathBuf) -> Response {
+    match fs::read(&path).await {
+        Ok(bytes) => {
+            let mut response = Response::new(Body::from(bytes));
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+            response
+        }
+        Err(_) => error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", path.display()),
+        ),
+    }
+}
+
+// Render a (possibly nested) folder page with child folders, files and upload form.
+async fn render_folder(folder: String, lang: &'static str) -> Response {
+    let t = |key| ui_text(lang, key);
+    let base_dir = json_path(&folder);
+    let folder_clone = folder.clone();
+    let (entries, children) = tokio::task::spawn_blocking(move || {
+        let children: Vec<String> = collect_subdirs(base_dir.clone())
+            .into_iter()
+            .filter(|child| !child.contains('/'))
+            .collect();
+        (collect_subdir_entries(base_dir, folder_clone), children)
+    })
+    .await
+    .unwrap_or_default();
+    let (top, nested) = match folder.split_once('/') {
+        Some((top, rest)) => (top.to_string(), Some(rest.to_string())),
+        None => (folder.clone(), None),
+    };
+    let back = match folder.rsplit_once('/') {
+        Some((parent, _)) => format!("/json/{}", parent),
+        None => "/json".to_string(),
+    };
 
-    let mut body = String::from(
-        "<!doctype html><html><head><meta charset=\"utf-8\"><title>JSON folder</title><style>
+    let mut body = format!("<!doctype html><html lang=\"{}\">", lang);
+    body.push_str(
+        "<head><meta charset=\"utf-8\"><title>JSON folder</title><style>
         :root{--bg:#0b0f1a;--card:#12192a;--accent:#ffb703;--text:#e5ecf4;--muted:#93a3b8;}
         *{box-sizing:border-box}body{margin:0;font-family:\"Space Grotesk\",system-ui,-apple-system,sans-serif;color:var(--text);
         background:radial-gradient(1200px 600px at 10% -10%, #1d2b4a 0%, transparent 60%),linear-gradient(180deg,#0b0f1a 0%,#0d1222 100%);}
@@ -348,23 +1092,99 @@ pub async fn subdir_index(Path(subdir): Path<String>) -> Response {
         label{display:block;margin-bottom:8px;color:var(--muted)}
         input[type=file],input[type=text]{width:100%;padding:10px;border-radius:10px;border:1px solid #1f2a44;background:#0d1425;color:var(--text)}
         button{margin-top:10px;background:var(--accent);border:none;color:#111;padding:10px 16px;border-radius:10px;font-weight:600;cursor:pointer}
-        </style></head><body><header><a href=\"/json\">← torna all'indice</a><h1>Cartella</h1></header><div class=\"wrap\">",
+        form.inline{display:inline}form.inline button{margin:0 0 0 6px;padding:2px 8px;font-size:12px;background:#ef476f;color:#fff}
+        </style></head><body><header>",
     );
+    body.push_str("<a href=\"");
+    body.push_str(&html_escape(&back));
+    body.push_str(&format!("\">{}</a><h1>{} <code>", t("back"), t("folder")));
+    body.push_str(&html_escape(&folder));
+    body.push_str("</code></h1></header><div class=\"wrap\">");
+    body.push_str(&readonly_notice(lang));
+
+    if !children.is_empty() {
+        body.push_str(&format!("<div class=\"card\"><h2>{}</h2><ul>", t("subdirs")));
+        for child in &children {
+            body.push_str("<li><a href=\"/json/");
+            body.push_str(&url_encode_path(&folder));
+            body.push('/');
+            body.push_str(&url_encode_path(child));
+            body.push_str("\">");
+            body.push_str(&html_escape(child));
+            body.push_str("/</a></li>");
+        }
+        body.push_str("</ul></div>");
+    }
 
-    body.push_str("<div class=\"card\"><h2>File disponibili</h2><ul>");
-    for (path, url) in entries {
-        body.push_str("<li><a href=\"");
-        body.push_str(&url);
-        body.push_str("\">");
-        body.push_str(&path);
-        body.push_str("</a></li>");
+    body.push_str(&format!("<div class=\"card\"><h2>{}</h2>", t("files")));
+    body.push_str(&file_filter(lang));
+    body.push_str("<ul class=\"file-list\">");
+    for entry in &entries {
+        body.push_str("<li>");
+        body.push_str(&file_link_html(entry));
+        body.push_str(&file_meta_html(entry));
+        body.push_str(" <a class=\"muted\" href=\"");
+        body.push_str(&entry.url);
+        body.push_str("?preview=1\">");
+        body.push_str(t("preview_lower"));
+        body.push_str("</a> <a class=\"muted\" href=\"");
+        body.push_str(&entry.url);
+        body.push_str("/edit\">");
+        body.push_str(t("edit_lower"));
+        body.push_str(&format!(
+            "</a> <form class=\"inline\" method=\"post\" action=\"/json/delete-file\" onsubmit=\"return confirm('{}')\"><input type=\"hidden\" name=\"path\" value=\"",
+            t("confirm_delete")
+        ));
+        body.push_str(&html_escape(&entry.path));
+        body.push_str(&format!(
+            "\"><button type=\"submit\">{}</button></form></li>",
+            t("delete_lower")
+        ));
     }
     body.push_str("</ul></div>");
 
-    body.push_str("<div class=\"card\"><h2>Upload</h2><form method=\"post\" enctype=\"multipart/form-data\">");
-    body.push_str("<label>Carica uno o piu file. Verranno salvati con il nome originale.</label>");
+    body.push_str(&format!(
+        "<div class=\"card\"><h2>{}</h2><form method=\"post\" enctype=\"multipart/form-data\" action=\"/json/",
+        t("upload")
+    ));
+    body.push_str(&html_escape(&top));
+    if let Some(nested) = &nested {
+        body.push_str("?dir=");
+        body.push_str(&html_escape(nested));
+    }
+    body.push_str("\">");
+    body.push_str(&format!("<label>{}</label>", t("upload_intro")));
     body.push_str("<input type=\"file\" name=\"files\" multiple>");
-    body.push_str("<button type=\"submit\">Carica</button></form></div></div></body></html>");
+    body.push_str(&format!("<input name=\"name\" placeholder=\"{}\">", t("save_as")));
+    body.push_str(&format!(
+        "<button type=\"submit\">{}</button></form></div>",
+        t("upload_submit")
+    ));
+
+    for (action, title, button) in [
+        ("/json/move", t("move_file"), t("move")),
+        ("/json/copy", t("copy_file"), t("copy")),
+    ] {
+        body.push_str("<div class=\"card\"><h2>");
+        body.push_str(title);
+        body.push_str("</h2><form method=\"post\" action=\"");
+        body.push_str(action);
+        body.push_str(&format!("\"><label>{}</label><select name=\"from\">", t("from")));
+        for entry in &entries {
+            body.push_str("<option>");
+            body.push_str(&html_escape(&entry.path));
+            body.push_str("</option>");
+        }
+        body.push_str(&format!(
+            "</select><label>{}</label><input type=\"text\" name=\"to\" placeholder=\"",
+            t("to")
+        ));
+        body.push_str(&html_escape(&folder));
+        body.push_str(&format!("/{}\" required><button type=\"submit\">", t("new_file")));
+        body.push_str(button);
+        body.push_str("</button></form></div>");
+    }
+    body.push_str("</div></body></html>");
 
     let mut response = Response::new(Body::from(body));
     response
@@ -377,72 +1197,309 @@ pub async fn subdir_index(Path(subdir): Path<String>) -> Response {
     response
 }
 
-// Handle multipart uploads into json/<subdir>.
-pub async fn upload_files(Path(subdir): Path<String>, mut multipart: Multipart) -> Response {
-    if !is_safe_segment(&subdir) {
-        return StatusCode::BAD_REQUEST.into_response();
+// Whether a multipart read failed because the body was cut at the request size cap.
+fn body_limit_exceeded(err: &axum::extract::multipart::MultipartError) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(current) = source {
+        if current.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = current.source();
     }
+    err.status() == StatusCode::PAYLOAD_TOO_LARGE
+}
+
+// Answer 413 for an upload whose body ran past the multipart request cap.
+fn upload_request_too_large() -> Response {
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload too large",
+        format!(
+            "upload requests are limited to {} bytes",
+            read_max_upload_request_bytes()
+        ),
+    )
+}
 
-    let dir = base_json_dir().join(&subdir);
-    if let Err(_) = fs::create_dir_all(&dir).await {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+// Handle multipart uploads into json/<subdir>, or json/<subdir>/<dir> with ?dir=.
+pub async fn upload_files(
+    Path(subdir): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Response {
+    if !is_safe_segment(&subdir) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe folder name", subdir),
+        );
+    }
+    if subdir == SCENARIOS_FOLDER {
+        return scenarios_folder_hidden();
     }
+    let folder = match form_value_utf8(&query.unwrap_or_default(), "dir") {
+        Some(dir) if !is_safe_dir_path(&dir) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid path",
+                format!("dir {} is not a safe folder path", dir),
+            );
+        }
+        Some(dir) => format!("{}/{}", subdir, dir),
+        None => subdir,
+    };
 
-    let mut saved_any = false;
-    while let Ok(Some(field)) = multipart.next_field().await {
+    let dir = json_path(&folder);
+    let limits = read_upload_limits(&folder);
+    let mut files = Vec::new();
+    let mut results = Vec::new();
+    let mut custom_name = None;
+    // Every part is read before anything is written, so a body cut at the cap saves nothing.
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(err) if body_limit_exceeded(&err) => return upload_request_too_large(),
+            Err(_) => break,
+        };
         let Some(file_name) = field.file_name().map(|s| s.to_string()) else {
+            if field.name() == Some("name")
+                && let Ok(name) = field.text().await
+                && !name.trim().is_empty()
+            {
+                custom_name = Some(name.trim().to_string());
+            }
             continue;
         };
-        if !is_safe_segment(&file_name) {
+        if file_name.is_empty() {
             continue;
         }
-        let Ok(bytes) = field.bytes().await else {
+        if !is_safe_segment(&file_name) {
+            results.push(UploadResult::new(file_name, "skipped", Some("unsafe name")));
             continue;
-        };
-        let path = dir.join(file_name);
-        if fs::write(path, bytes).await.is_ok() {
-            saved_any = true;
+        }
+        let mut bytes = Vec::new();
+        let mut read_failed = false;
+        loop {
+            match field.chunk().await {
+                Ok(Some(chunk)) => {
+                    if bytes.len() + chunk.len() > limits.max_bytes {
+                        return json_response(
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                            serde_json::json!({
+                                "error": "file too large",
+                                "detail": format!(
+                                    "{} exceeds the {} bytes allowed per file in {}",
+                                    file_name, limits.max_bytes, folder
+                                ),
+                            }),
+                        );
+                    }
+                    bytes.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(err) if body_limit_exceeded(&err) => return upload_request_too_large(),
+                Err(_) => {
+                    read_failed = true;
+                    break;
+                }
+            }
+        }
+        if read_failed {
+            results.push(UploadResult::new(file_name, "failed", Some("read error")));
+        } else {
+            files.push((file_name, bytes));
         }
     }
 
-    if !saved_any {
-        return StatusCode::BAD_REQUEST.into_response();
+    if let Some(name) = custom_name {
+        if !is_safe_segment(&name) {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid name",
+                format!("{} is not a safe file name", name),
+            );
+        }
+        if let [(file_name, _)] = files.as_mut_slice() {
+            *file_name = name;
+        }
     }
 
-    Redirect::to(&format!("/json/{}", subdir)).into_response()
-}
+    // Sort by name so repeated names resolve the same way every time: the last part wins.
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut unique: Vec<(String, Vec<u8>)> = Vec::new();
+    for (file_name, bytes) in files {
+        if let Some(last) = unique.last_mut()
+            && last.0 == file_name
+        {
+            results.push(UploadResult::new(file_name, "skipped", Some("duplicate")));
+            last.1 = bytes;
+            continue;
+        }
+        unique.push((file_name, bytes));
+    }
 
-// Return refresh-token JSON response from file or fallback.
-pub async fn refresh_token() -> Response {
-    let path = base_json_dir().join("authentication").join("refresh.json");
-    let bytes = match fs::read(&path).await {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            let fallback = r#"{"status":"success","data":{"access_token":"dev_access_token"}} "#;
-            fallback.as_bytes().to_vec()
+    if let Some(max_files) = limits.max_files {
+        let existing: Vec<String> = collect_subdir_entries(dir.clone(), folder.clone())
+            .into_iter()
+            .filter_map(|entry| entry.path.rsplit('/').next().map(str::to_string))
+            .filter(|name| name != ".limits")
+            .collect();
+        let added = unique
+            .iter()
+            .filter(|(name, _)| !existing.contains(name))
+            .count();
+        if existing.len() + added > max_files {
+            return json_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                serde_json::json!({
+                    "error": "too many files",
+                    "detail": format!(
+                        "{} allows at most {} files; it has {} and the upload adds {}",
+                        folder, max_files, existing.len(), added
+                    ),
+                }),
+            );
         }
-    };
+    }
 
-    let mut response = Response::new(Body::from(bytes));
-    response
-        .headers_mut()
-        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        HeaderValue::from_static("no-store"),
-    );
-    response
-}
+    if let Err(err) = fs::create_dir_all(&dir).await {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "create failed",
+            format!("{}: {}", folder, err),
+        );
+    }
+
+    let validate = read_validate_uploads();
+    let require_ext = read_require_json_ext();
+    let mut invalid = Vec::new();
+    let mut wrong_ext = Vec::new();
+    for (file_name, bytes) in unique {
+        let fixture = has_fixture_extension(&file_name);
+        if require_ext && !fixture {
+            results.push(UploadResult::new(file_name.clone(), "skipped", Some("not a .json file")));
+            wrong_ext.push(file_name);
+            continue;
+        }
+        if validate
+            && file_name.to_lowercase().ends_with(".json")
+            && serde_json::from_slice::<serde_json::Value>(&bytes).is_err()
+        {
+            results.push(UploadResult::new(file_name.clone(), "skipped", Some("invalid json")));
+            invalid.push(file_name);
+            continue;
+        }
+        let outcome = match write_atomic_async(dir.join(&file_name), bytes).await {
+            Ok(()) if !fixture => UploadResult::new(file_name, "saved", Some("not a .json file")),
+            Ok(()) => UploadResult::new(file_name, "saved", None),
+            Err(_) => UploadResult::new(file_name, "failed", Some("write error")),
+        };
+        results.push(outcome);
+    }
+    results.sort_by(|a, b| a.file.cmp(&b.file));
+    let saved_any = results.iter().any(|r| r.status == "saved");
+
+    let wants_json = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/json"));
+    if wants_json {
+        let status = if saved_any {
+            StatusCode::OK
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        let results: Vec<_> = results.iter().map(UploadResult::to_json).collect();
+        return json_response(status, serde_json::json!({ "results": results }));
+    }
+
+    if !wrong_ext.is_empty() {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            serde_json::json!({
+                "error": "wrong extension",
+                "detail": "uploads must end in .json, .csv, .yaml or .yml",
+                "skipped": wrong_ext,
+            }),
+        );
+    }
+
+    if !invalid.is_empty() {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            serde_json::json!({
+                "error": "invalid json",
+                "detail": "some files are not valid JSON",
+                "invalid": invalid,
+            }),
+        );
+    }
+
+    if !saved_any {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "nothing saved",
+            "no file in the upload could be saved",
+        );
+    }
+
+    Redirect::to(&format!("/json/{}", folder)).into_response()
+}
+
+// Outcome of one uploaded part.
+struct UploadResult {
+    file: String,
+    status: &'static str,
+    reason: Option<&'static str>,
+}
+
+impl UploadResult {
+    // Build an outcome for a file.
+    fn new(file: String, status: &'static str, reason: Option<&'static str>) -> Self {
+        Self { file, status, reason }
+    }
+
+    // Serialize the outcome, omitting an empty reason.
+    fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({ "file": self.file, "status": self.status });
+        if let Some(reason) = self.reason {
+            value["reason"] = serde_json::Value::from(reason);
+        }
+        value
+    }
+}
+
+// Return refresh-token JSON response from file or fallback.
+pub async fn refresh_token() -> Response {
+    let path = base_json_dir().join("authentication").join("refresh.json");
+    let bytes = match fs::read(&path).await {
+        Ok(bytes) => bytes,
+        Err(_) => read_fallback_json(
+            "refresh_fallback.json",
+            r#"{"status":"success","data":{"access_token":"dev_access_token"}} "#,
+        ),
+    };
+    let bytes = fill_refresh_placeholders(bytes, read_refresh_expiry());
+
+    let mut response = Response::new(Body::from(bytes));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
 
 // Return ping JSON response from file or fallback.
 pub async fn ping_response() -> Response {
     let path = base_json_dir().join("ping").join("response.json");
     let bytes = match fs::read(&path).await {
         Ok(bytes) => bytes,
-        Err(_) => {
-            let fallback = r#"{"status":"success"}"#;
-            fallback.as_bytes().to_vec()
-        }
+        Err(_) => read_fallback_json("ping_fallback.json", r#"{"status":"success"}"#),
     };
 
     let mut response = Response::new(Body::from(bytes));
@@ -457,52 +1514,552 @@ pub async fn ping_response() -> Response {
 }
 
 // Route GET /api/* to ping or mapped JSON files.
-pub async fn api_get(Path(path): Path<String>) -> Response {
+pub async fn api_get(
+    Path(path): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
     let requested = format!("/api/{}", path);
     if read_ping_endpoint() == requested {
         return ping_response().await;
     }
 
-    if let Some(file) = find_route_mapping("GET", &requested) {
-        return serve_mapped_json(&file).await;
+    let query = query.unwrap_or_default();
+    if let Some(mapping) = find_route_mapping("GET", &requested) {
+        assert_expectation(&mapping, &headers, None).await;
+        return serve_mapped_json(&mapping, &query, &headers).await;
     }
 
-    StatusCode::NOT_FOUND.into_response()
+    if let Some(response) = method_not_allowed("GET", &requested) {
+        return response;
+    }
+
+    if let Some(upstream) = read_upstream() {
+        return proxy_upstream(&upstream, "GET", &requested, &query, &headers, Vec::new()).await;
+    }
+
+    api_fallback().await
+}
+
+// Stream the GET mapping of the matching /api/* path as server-sent events.
+pub async fn sse_stub(Path(path): Path<String>) -> Response {
+    match stub_fixture(&path).await {
+        Ok((mapping, bytes)) => stream_sse_events(&mapping, &bytes),
+        Err(response) => response,
+    }
+}
+
+// Look up the GET mapping behind a /sse or /ws stub path and read its fixture, both safety-checked.
+async fn stub_fixture(path: &str) -> Result<(RouteMapping, Vec<u8>), Response> {
+    if path.is_empty() || !is_safe_rel_path(path) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe path", path),
+        ));
+    }
+    let requested = format!("/api/{}", path);
+    let Some(mapping) = find_route_mapping("GET", &requested) else {
+        return Err(error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("no GET mapping for {}", requested),
+        ));
+    };
+    let file = resolve_mapped_file(&mapping);
+    if !is_safe_rel_path(file) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe path under json/", file),
+        ));
+    }
+    match fs::read(scenario_file_path(file)).await {
+        Ok(bytes) => Ok((mapping, bytes)),
+        Err(err) => Err(fixture_read_failed(file, err)),
+    }
+}
+
+// Fixture that answered a mapped request, attached to the response for the access log.
+#[derive(Clone)]
+struct ServedFile {
+    file: String,
+    bytes: usize,
+}
+
+// Upgrade /ws/* to a WebSocket fed by the GET mapping of the matching /api/* path.
+pub async fn ws_stub(Path(path): Path<String>, ws: WebSocketUpgrade) -> Response {
+    let (mapping, bytes) = match stub_fixture(&path).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+
+    let messages = parse_sse_events(&bytes);
+    let interval = mapping_interval(&mapping);
+    ws.on_upgrade(move |socket| ws_session(socket, messages, interval))
 }
 
 // Route POST /api/* to refresh or mapped JSON files.
-pub async fn api_post(Path(path): Path<String>) -> Response {
+pub async fn api_post(
+    Path(path): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
     let requested = format!("/api/{}", path);
     if read_refresh_endpoint() == requested {
         return refresh_token().await;
     }
 
-    if let Some(file) = find_route_mapping("POST", &requested) {
-        return serve_mapped_json(&file).await;
+    if let Some(mapping) = find_route_mapping("POST", &requested) {
+        let limit = mapping
+            .option("max_body")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or_else(read_max_body_bytes);
+        let declared = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if declared.is_some_and(|len| len > limit) {
+            return payload_too_large(&mapping).await;
+        }
+        let Ok(bytes) = axum::body::to_bytes(body, limit).await else {
+            return payload_too_large(&mapping).await;
+        };
+        assert_expectation(&mapping, &headers, Some(&bytes)).await;
+        return serve_mapped_json(&mapping, &query.unwrap_or_default(), &headers).await;
+    }
+
+    if let Some(response) = method_not_allowed("POST", &requested) {
+        return response;
+    }
+
+    if let Some(upstream) = read_upstream() {
+        let Ok(bytes) = axum::body::to_bytes(body, read_max_body_bytes()).await else {
+            return body_too_large();
+        };
+        let query = query.unwrap_or_default();
+        return proxy_upstream(&upstream, "POST", &requested, &query, &headers, bytes.to_vec()).await;
+    }
+
+    api_fallback().await
+}
+
+// Serve POST /graphql from the file mapped to the operation name or query hash.
+pub async fn graphql(RawQuery(query): RawQuery, headers: HeaderMap, body: Body) -> Response {
+    let Ok(bytes) = axum::body::to_bytes(body, read_max_body_bytes()).await else {
+        return body_too_large();
+    };
+    let Ok(request) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return graphql_error(StatusCode::BAD_REQUEST, "request body must be JSON", "BAD_REQUEST", &[]);
+    };
+    let keys = graphql_operation_keys(&request);
+    if keys.is_empty() {
+        return graphql_error(
+            StatusCode::BAD_REQUEST,
+            "missing operationName and query",
+            "BAD_REQUEST",
+            &[],
+        );
+    }
+    let mappings = read_graphql_mappings();
+    let found = keys
+        .iter()
+        .find_map(|key| mappings.iter().find(|(k, _)| k == key));
+    match found {
+        Some((_, mapping)) => serve_mapped_json(mapping, &query.unwrap_or_default(), &headers).await,
+        None => graphql_error(
+            StatusCode::OK,
+            &format!("unknown operation {}", keys[0]),
+            "OPERATION_NOT_MAPPED",
+            &keys,
+        ),
+    }
+}
+
+// Build a GraphQL-shaped error envelope listing the keys that were tried.
+fn graphql_error(status: StatusCode, message: &str, code: &str, keys: &[String]) -> Response {
+    json_response(
+        status,
+        serde_json::json!({
+            "data": null,
+            "errors": [{ "message": message, "extensions": { "code": code, "keys": keys } }],
+        }),
+    )
+}
+
+// Check the request against the mapping's expect file, logging ASSERT FAIL on mismatch.
+async fn assert_expectation(mapping: &RouteMapping, headers: &HeaderMap, body: Option<&[u8]>) {
+    let Some(file) = mapping.option("expect") else {
+        return;
+    };
+    let failures = match fs::read(json_path(file)).await {
+        Ok(bytes) => match serde_json::from_slice::<serde_json::Value>(&bytes) {
+            Ok(expect) => check_expectation(
+                &expect,
+                |name| {
+                    headers
+                        .get(name)
+                        .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+                },
+                body,
+            ),
+            Err(_) => vec![format!("expect file {} is not valid JSON", file)],
+        },
+        Err(_) => vec![format!("expect file {} not found", file)],
+    };
+    record_assertion(&mapping.method, &mapping.path, &failures);
+    if !failures.is_empty() {
+        let mut entry = LogEntry::new("ASSERT FAIL");
+        entry.method = Some(mapping.method.clone());
+        entry.path = Some(mapping.path.clone());
+        entry.detail = Some(failures.join("; "));
+        log_line(entry);
+    }
+}
+
+// Report request expectation counters.
+pub async fn get_assertions() -> Response {
+    json_response(StatusCode::OK, assertions_snapshot())
+}
+
+// Forward an unmapped /api request to the configured upstream and relay its response.
+async fn proxy_upstream(
+    upstream: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &HeaderMap,
+    body: Vec<u8>,
+) -> Response {
+    let mut url = format!("{}{}", upstream, path);
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(query);
+    }
+    let method = reqwest::Method::from_bytes(method.as_bytes()).unwrap_or(reqwest::Method::GET);
+    let request = upstream_client()
+        .request(method.clone(), &url)
+        .headers(upstream_headers(headers, read_api_token().as_deref()))
+        .body(body);
+
+    let upstream_response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            tracing::error!(error = %err, url = %url, "upstream request failed");
+            let mut entry = LogEntry::new("PROXY");
+            entry.status = Some(StatusCode::BAD_GATEWAY.as_u16());
+            entry.method = Some(method.to_string());
+            entry.path = Some(path.to_string());
+            entry.detail = Some(format!("-> {} failed", url));
+            log_line(entry);
+            return error_response(
+                StatusCode::BAD_GATEWAY,
+                "upstream unavailable",
+                format!("{}: {}", url, err),
+            );
+        }
+    };
+
+    let status = upstream_response.status();
+    let upstream_headers = upstream_response.headers().clone();
+    let bytes = upstream_response.bytes().await.unwrap_or_default();
+
+    let mut entry = LogEntry::new("PROXY");
+    entry.status = Some(status.as_u16());
+    entry.method = Some(method.to_string());
+    entry.path = Some(path.to_string());
+    entry.detail = Some(format!("-> {}", url));
+    log_line(entry);
+
+    // Read-only mode also freezes recording, so proxied traffic cannot rewrite routes.
+    if status.is_success() && read_record_enabled() && !read_readonly() {
+        record_fixture(method.as_str(), path, &bytes).await;
+    }
+
+    let mut response = Response::new(Body::from(bytes));
+    *response.status_mut() = status;
+    for (name, value) in &upstream_headers {
+        if name != header::CONTENT_LENGTH && !is_hop_by_hop_header(name.as_str()) {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response
+}
+
+// Save a proxied response as a fixture and map the route to it.
+async fn record_fixture(method: &str, path: &str, bytes: &[u8]) {
+    let Some(derived) = derive_fixture_path("recorded", path) else {
+        return;
+    };
+    let mut file = format!("{}.json", derived);
+    let mut suffix = 1;
+    while fs::try_exists(json_path(&file)).await.unwrap_or(false) {
+        file = format!("{}-{}.json", derived, suffix);
+        suffix += 1;
+    }
+
+    let target = json_path(&file);
+    if let Some(parent) = target.parent()
+        && fs::create_dir_all(parent).await.is_err()
+    {
+        return;
+    }
+    if write_atomic_async(target, bytes.to_vec()).await.is_err() {
+        return;
+    }
+
+    let mut mappings = read_route_mappings();
+    mappings.retain(|m| !(m.method == method && m.path == path));
+    mappings.push(RouteMapping {
+        method: method.to_string(),
+        path: path.to_string(),
+        file: file.clone(),
+        options: Vec::new(),
+    });
+    if write_route_mappings(&mappings).is_ok() {
+        let mut entry = LogEntry::new("RECORD");
+        entry.method = Some(method.to_string());
+        entry.path = Some(path.to_string());
+        entry.detail = Some(format!("-> {}", file));
+        log_line(entry);
+    }
+}
+
+// Request headers forwarded upstream: no hop-by-hop headers, framing or the stub's own token.
+fn upstream_headers(headers: &HeaderMap, stub_token: Option<&str>) -> HeaderMap {
+    let listed: Vec<String> = headers
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim().to_ascii_lowercase())
+        .collect();
+    let mut forwarded = HeaderMap::new();
+    for (name, value) in headers {
+        if name == header::HOST
+            || name == header::CONTENT_LENGTH
+            || is_hop_by_hop_header(name.as_str())
+            || listed.iter().any(|l| l == name.as_str())
+        {
+            continue;
+        }
+        if name == header::AUTHORIZATION
+            && let Some(token) = stub_token
+            && value
+                .to_str()
+                .ok()
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .is_some_and(|v| v.trim() == token)
+        {
+            continue;
+        }
+        forwarded.append(name, value.clone());
+    }
+    forwarded
+}
+
+// Time allowed to open a connection to the upstream.
+const UPSTREAM_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Time allowed for a whole proxied request, body included.
+const UPSTREAM_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Shared HTTP client for upstream passthrough, with timeouts so a dead upstream fails fast.
+fn upstream_client() -> &'static reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(UPSTREAM_CONNECT_TIMEOUT)
+            .timeout(UPSTREAM_REQUEST_TIMEOUT)
+            .build()
+            .expect("failed to build upstream client")
+    })
+}
+
+// Return 413 with the mapping's configured body or a default JSON error.
+async fn payload_too_large(mapping: &RouteMapping) -> Response {
+    let configured = match mapping.option("max_body_file") {
+        Some(file) => fs::read(json_path(file)).await.ok(),
+        None => None,
+    };
+    let bytes = configured
+        .unwrap_or_else(|| br#"{"error":"payload too large"}"#.to_vec());
+
+    let mut response = Response::new(Body::from(bytes));
+    *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Build a Content-Type header, falling back to JSON if the value is not a valid header.
+fn content_type_value(content_type: &str) -> HeaderValue {
+    HeaderValue::from_str(content_type).unwrap_or(HeaderValue::from_static("application/json"))
+}
+
+// Read every file a merge manifest names into one object; also returns the newest mtime.
+async fn merge_manifest(
+    bytes: &[u8],
+) -> Result<(Vec<u8>, Option<std::time::SystemTime>), String> {
+    let mut merged = serde_json::Map::new();
+    let mut newest = None;
+    for (key, file) in manifest_components(bytes)? {
+        let path = scenario_file_path(&file);
+        let bytes = fs::read(&path)
+            .await
+            .map_err(|err| format!("merge component {} ({}) could not be read: {}", key, file, err))?;
+        let bytes = convert_fixture(&file, bytes)?;
+        let value = serde_json::from_slice(&bytes)
+            .map_err(|err| format!("merge component {} ({}) is not JSON: {}", key, file, err))?;
+        merged.insert(key, value);
+        newest = newest.max(file_modified(&path).await);
+    }
+    let merged = serde_json::to_vec(&serde_json::Value::Object(merged))
+        .map_err(|err| err.to_string())?;
+    Ok((merged, newest))
+}
+
+// Answer 500 with the reason a fixture could not be converted to JSON.
+fn conversion_error(error: String) -> Response {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, "invalid fixture", error)
+}
+
+// Read the JSONP callback from ?callback=, rejecting unsafe names with 400.
+fn jsonp_callback(query: &str) -> Result<Option<String>, StatusCode> {
+    match form_value(query, "callback") {
+        None => Ok(None),
+        Some(callback) if is_safe_js_callback(&callback) => Ok(Some(callback)),
+        Some(_) => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+// Methods answered at an /api path: ping/refresh plus GET/POST mappings.
+fn allowed_methods(path: &str) -> Vec<&'static str> {
+    ["GET", "POST"]
+        .into_iter()
+        .filter(|method| {
+            shadowing_builtin(method, path).is_some() || find_route_mapping(method, path).is_some()
+        })
+        .collect()
+}
+
+// Answer OPTIONS /api/* with 204 and an Allow header listing the served methods.
+pub async fn api_options(Path(path): Path<String>, headers: HeaderMap) -> Response {
+    let path = format!("/api/{}", path);
+    let allowed = allowed_methods(&path);
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    if let Ok(value) = HeaderValue::from_str(&allowed.join(", ")) {
+        response.headers_mut().insert(header::ALLOW, value.clone());
+        if let Some(mapping) = preflight_mapping(&path, &headers) {
+            set_cors_origin(&mut response, &mapping, &headers);
+            let cors = response.headers_mut();
+            if cors.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN) {
+                cors.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value);
+                if let Some(requested) = headers.get(header::ACCESS_CONTROL_REQUEST_HEADERS) {
+                    cors.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, requested.clone());
+                }
+            }
+        }
+    }
+    response
+}
+
+// Mapping a CORS preflight asks about through Access-Control-Request-Method.
+fn preflight_mapping(path: &str, headers: &HeaderMap) -> Option<RouteMapping> {
+    let method = headers
+        .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|v| v.to_str().ok())?;
+    if shadowing_builtin(method, path).is_some() {
+        return None;
+    }
+    find_route_mapping(method, path)
+}
+
+// Return 405 with Allow when the path is served only under other methods.
+fn method_not_allowed(method: &str, path: &str) -> Option<Response> {
+    let allowed: Vec<&str> = allowed_methods(path)
+        .into_iter()
+        .filter(|other| *other != method)
+        .collect();
+    if allowed.is_empty() {
+        return None;
+    }
+
+    let mut response = StatusCode::METHOD_NOT_ALLOWED.into_response();
+    if let Ok(value) = HeaderValue::from_str(&allowed.join(", ")) {
+        response.headers_mut().insert(header::ALLOW, value);
     }
+    Some(response)
+}
+
+// Serve the configured fallback fixture for unmatched /api routes, or a bare 404.
+async fn api_fallback() -> Response {
+    let Some(file) = read_api_fallback() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(bytes) = fs::read(json_path(file)).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
 
-    StatusCode::NOT_FOUND.into_response()
+    let mut response = Response::new(Body::from(bytes));
+    *response.status_mut() =
+        StatusCode::from_u16(read_api_fallback_status()).unwrap_or(StatusCode::NOT_FOUND);
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
 }
 
 // Persist configurable refresh endpoint.
 pub async fn set_refresh_endpoint(body: String) -> Response {
     let Some(path) = form_value(&body, "path") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field path is required",
+        );
     };
     if !path.starts_with("/api/") {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} must start with /api/", path),
+        );
     }
     if !is_safe_rel_path(path.trim_start_matches('/')) {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe path", path),
+        );
     }
-
-    let config_dir = base_config_dir();
-    if fs::create_dir_all(&config_dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if let Some(mapping) = read_route_mappings()
+        .into_iter()
+        .find(|m| m.method == "POST" && m.path == path)
+    {
+        return error_response(
+            StatusCode::CONFLICT,
+            "mapping conflict",
+            format!(
+                "POST {} is mapped to {}; the refresh endpoint is answered before any mapping, \
+                 so that mapping would stop working. Delete it or choose another path",
+                path, mapping.file
+            ),
+        );
     }
-    let file_path = config_dir.join("refresh_endpoint.txt");
-    if fs::write(file_path, path).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    if let Err(err) = write_config_file("refresh_endpoint.txt", path) {
+        return config_write_failed("refresh_endpoint.txt", err);
     }
 
     Redirect::to("/json").into_response()
@@ -511,48 +2068,69 @@ pub async fn set_refresh_endpoint(body: String) -> Response {
 // Persist configurable ping endpoint.
 pub async fn set_ping_endpoint(body: String) -> Response {
     let Some(path) = form_value(&body, "path") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field path is required",
+        );
     };
     if !path.starts_with("/api/") {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} must start with /api/", path),
+        );
     }
     if !is_safe_rel_path(path.trim_start_matches('/')) {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe path", path),
+        );
     }
-
-    let config_dir = base_config_dir();
-    if fs::create_dir_all(&config_dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if let Some(mapping) = read_route_mappings()
+        .into_iter()
+        .find(|m| m.method == "GET" && m.path == path)
+    {
+        return error_response(
+            StatusCode::CONFLICT,
+            "mapping conflict",
+            format!(
+                "GET {} is mapped to {}; the ping endpoint is answered before any mapping, \
+                 so that mapping would stop working. Delete it or choose another path",
+                path, mapping.file
+            ),
+        );
     }
-    let file_path = config_dir.join("ping_endpoint.txt");
-    if fs::write(file_path, path).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    if let Err(err) = write_config_file("ping_endpoint.txt", path) {
+        return config_write_failed("ping_endpoint.txt", err);
     }
 
     Redirect::to("/json").into_response()
 }
 
-// Persist list of log-ignored paths.
+// Persist list of log-ignored paths (one per line, or repeated patterns fields).
 pub async fn set_log_ignore(body: String) -> Response {
-    let Some(patterns) = form_value(&body, "patterns") else {
-        return StatusCode::BAD_REQUEST.into_response();
-    };
+    let fields = form_values(&body, "patterns");
+    if fields.is_empty() {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field patterns is required",
+        );
+    }
 
     let mut lines = Vec::new();
-    for line in patterns.lines() {
+    for line in fields.iter().flat_map(|patterns| patterns.lines()) {
         if let Some(normalized) = normalize_log_pattern(line) {
             lines.push(normalized);
         }
     }
 
-    let config_dir = base_config_dir();
-    if fs::create_dir_all(&config_dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
-    let file_path = config_dir.join("log_ignore.txt");
     let data = lines.join("\n");
-    if fs::write(file_path, data).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if let Err(err) = write_config_file("log_ignore.txt", data) {
+        return config_write_failed("log_ignore.txt", err);
     }
 
     Redirect::to("/json").into_response()
@@ -561,17 +2139,16 @@ pub async fn set_log_ignore(body: String) -> Response {
 // Enable or disable logging globally.
 pub async fn set_log_toggle(body: String) -> Response {
     let Some(value) = form_value(&body, "enabled") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field enabled is required",
+        );
     };
     let enabled = value.trim().eq_ignore_ascii_case("on");
-    let config_dir = base_config_dir();
-    if fs::create_dir_all(&config_dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
-    let file_path = config_dir.join("log_enabled.txt");
     let data = if enabled { "on" } else { "off" };
-    if fs::write(file_path, data).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if let Err(err) = write_config_file("log_enabled.txt", data) {
+        return config_write_failed("log_enabled.txt", err);
     }
 
     Redirect::to("/json").into_response()
@@ -580,153 +2157,1384 @@ pub async fn set_log_toggle(body: String) -> Response {
 // Persist mapping from API path+method to JSON file.
 pub async fn set_route_mapping(body: String) -> Response {
     let Some(method) = form_value(&body, "method") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field method is required",
+        );
     };
     let Some(path) = form_value(&body, "path") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field path is required",
+        );
     };
     let Some(file) = form_value(&body, "file") else {
-        return StatusCode::BAD_REQUEST.into_response();
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing field",
+            "form field file is required",
+        );
     };
-
-    let method = method.trim().to_uppercase();
-    if method != "GET" && method != "POST" {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-
-    let path = path.trim().to_string();
-    if !path.starts_with("/api/") {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    if !is_safe_rel_path(path.trim_start_matches('/')) {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-
-    let file = match normalize_json_file(&file) {
-        Ok(value) => value,
-        Err(response) => return response,
+    let options = form_value(&body, "options").unwrap_or_default();
+    let Some(mapping) = build_route_mapping(&method, &path, &file, options.split_whitespace())
+    else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid mapping",
+            format!("{} {} {} {} is not a valid mapping", method, path, file, options),
+        );
     };
-    if !is_safe_rel_path(&file) {
-        return StatusCode::BAD_REQUEST.into_response();
+    if let Some(builtin) = shadowing_builtin(&mapping.method, &mapping.path) {
+        return error_response(
+            StatusCode::CONFLICT,
+            "mapping conflict",
+            format!(
+                "{} {} is the {} endpoint, which is answered before any mapping; \
+                 choose another path or move the {} endpoint first",
+                mapping.method, mapping.path, builtin, builtin
+            ),
+        );
     }
-
     let mut mappings = read_route_mappings();
-    mappings.retain(|m| !(m.method == method && m.path == path));
-    mappings.push(RouteMapping { method, path, file });
-    if write_route_mappings(&mappings).is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    mappings.retain(|m| !(m.method == mapping.method && m.path == mapping.path));
+    mappings.push(mapping);
+    if let Err(err) = write_route_mappings(&mappings) {
+        return config_write_failed("routes.txt", err);
     }
 
     Redirect::to("/json").into_response()
 }
 
-// Create a new subdirectory under json/.
-pub async fn create_subdir(body: String) -> Response {
+// Activate a named scenario, or return to base mappings when empty.
+pub async fn set_scenario(body: String) -> Response {
     let name = form_value(&body, "name").unwrap_or_default();
-
-    if !is_safe_segment(&name) {
-        return StatusCode::BAD_REQUEST.into_response();
+    let name = name.trim();
+    if !name.is_empty() && !read_scenario_names().iter().any(|n| n == name) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "unknown scenario",
+            format!("no scenario named {}", name),
+        );
     }
 
-    let dir = base_json_dir().join(&name);
-    if let Err(_) = fs::create_dir_all(&dir).await {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if let Err(err) = write_config_file("scenario.txt", name) {
+        return config_write_failed("scenario.txt", err);
     }
 
     Redirect::to("/json").into_response()
 }
 
-// Delete a subdirectory under json/.
-pub async fn delete_subdir(body: String) -> Response {
-    let name = form_value(&body, "name").unwrap_or_default();
-    if !is_safe_segment(&name) {
-        return StatusCode::BAD_REQUEST.into_response();
+// Import an uploaded OpenAPI spec, creating a fixture and mapping per operation.
+pub async fn import_openapi(mut multipart: Multipart) -> Response {
+    let mut spec = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if let Ok(bytes) = field.bytes().await
+            && !bytes.is_empty()
+        {
+            spec = Some(bytes);
+            break;
+        }
     }
+    let Some(spec) = spec else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "missing file",
+            "upload a non-empty OpenAPI document",
+        );
+    };
+    let (operations, skipped) = match parse_openapi_operations(&spec) {
+        Ok(result) => result,
+        Err(err) => {
+            return error_response(StatusCode::BAD_REQUEST, "invalid OpenAPI document", err);
+        }
+    };
 
-    let dir = base_json_dir().join(&name);
-    if fs::remove_dir_all(&dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut mappings = read_route_mappings();
+    let mut created = 0;
+    for operation in operations {
+        let Some(derived) = derive_fixture_path("openapi", &operation.path) else {
+            continue;
+        };
+        let file = format!("{}.{}.json", derived, operation.method.to_lowercase());
+        let target = json_path(&file);
+        if let Some(parent) = target.parent()
+            && let Err(err) = fs::create_dir_all(parent).await
+        {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "create failed",
+                format!("{}: {}", file, err),
+            );
+        }
+        let bytes = serde_json::to_vec_pretty(&operation.example).unwrap_or_default();
+        if let Err(err) = write_atomic_async(target, bytes).await {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "write failed",
+                format!("{}: {}", file, err),
+            );
+        }
+        mappings.retain(|m| !(m.method == operation.method && m.path == operation.path));
+        mappings.push(RouteMapping {
+            method: operation.method,
+            path: operation.path,
+            file,
+            options: Vec::new(),
+        });
+        created += 1;
+    }
+    if let Err(err) = write_route_mappings(&mappings) {
+        return config_write_failed("routes.txt", err);
     }
 
-    Redirect::to("/json").into_response()
+    axum::Json(serde_json::json!({ "created": created, "skipped": skipped })).into_response()
 }
 
-// Rename a subdirectory under json/.
-pub async fn rename_subdir(body: String) -> Response {
-    let from = form_value(&body, "from").unwrap_or_default();
-    let to = form_value(&body, "to").unwrap_or_default();
-    if !is_safe_segment(&from) || !is_safe_segment(&to) {
-        return StatusCode::BAD_REQUEST.into_response();
+// Describe the served routes as a minimal OpenAPI 3.0 document.
+pub async fn export_openapi() -> Response {
+    let mut routes: Vec<(String, String, String)> = vec![
+        ("GET".to_string(), read_ping_endpoint(), "ping/response.json".to_string()),
+        (
+            "POST".to_string(),
+            read_refresh_endpoint(),
+            "authentication/refresh.json".to_string(),
+        ),
+    ];
+    routes.extend(read_route_mappings().into_iter().map(|m| (m.method, m.path, m.file)));
+
+    let mut paths = serde_json::Map::new();
+    for (method, path, file) in routes {
+        let schema = match fs::read(json_path(&file)).await {
+            Ok(bytes) => serde_json::from_slice::<serde_json::Value>(&bytes)
+                .map(|value| infer_json_schema(&value))
+                .unwrap_or_else(|_| serde_json::json!({})),
+            Err(_) => serde_json::json!({}),
+        };
+        let operation = serde_json::json!({
+            "summary": format!("Served from json/{}", file),
+            "responses": {
+                "200": {
+                    "description": "Stub response",
+                    "content": { "application/json": { "schema": schema } }
+                }
+            }
+        });
+        let item = paths
+            .entry(path)
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(item) = item.as_object_mut() {
+            item.insert(method.to_lowercase(), operation);
+        }
     }
 
-    let from_dir = base_json_dir().join(&from);
-    let to_dir = base_json_dir().join(&to);
-    if fs::rename(from_dir, to_dir).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
+    let document = serde_json::json!({
+        "openapi": "3.0.3",
+        "info": { "title": "JsonStub", "version": env!("CARGO_PKG_VERSION") },
+        "paths": paths,
+    });
+    axum::Json(document).into_response()
+}
+
+// Export the served routes as an importable Postman v2.1 collection.
+pub async fn export_postman() -> Response {
+    let mut routes: Vec<(String, String)> = vec![
+        ("GET".to_string(), read_ping_endpoint()),
+        ("POST".to_string(), read_refresh_endpoint()),
+    ];
+    routes.extend(read_route_mappings().into_iter().map(|m| (m.method, m.path)));
+
+    let items: Vec<serde_json::Value> = routes
+        .into_iter()
+        .map(|(method, path)| {
+            let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+            serde_json::json!({
+                "name": format!("{} {}", method, path),
+                "request": {
+                    "method": method,
+                    "header": [],
+                    "url": {
+                        "raw": format!("{{{{baseUrl}}}}{}", path),
+                        "host": ["{{baseUrl}}"],
+                        "path": segments,
+                    }
+                }
+            })
+        })
+        .collect();
+    let collection = serde_json::json!({
+        "info": {
+            "name": "ApiFileStub",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "item": items,
+        "variable": [{ "key": "baseUrl", "value": base_url() }],
+    });
+
+    let mut response = axum::Json(collection).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"apifilestub.postman_collection.json\""),
+    );
+    response
+}
+
+// Create a new (possibly nested) subdirectory under json/.
+pub async fn create_subdir(body: String) -> Response {
+    let name = form_value_utf8(&body, "name").unwrap_or_default();
+    let name = name.trim_matches('/');
+
+    if !is_safe_dir_path(name) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe folder path", name),
+        );
+    }
+
+    if let Some(response) = folder_name_conflict(name).await {
+        return response;
+    }
+    let dir = json_path(name);
+    let result = fs::create_dir_all(&dir).await;
+    bump_fs_generation();
+    if let Err(err) = result {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "create failed",
+            format!("{}: {}", name, err),
+        );
+    }
+
+    Redirect::to("/json").into_response()
+}
+
+// Refuse folder paths whose URL would hit a built-in /json route or that run into a file.
+async fn folder_name_conflict(name: &str) -> Option<Response> {
+    let top = name.split('/').next().unwrap_or_default();
+    if let Some(response) = reserved_folder(top) {
+        return Some(response);
+    }
+    let mut prefix = String::new();
+    for segment in name.split('/') {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(segment);
+        if fs::metadata(json_path(&prefix)).await.is_ok_and(|m| m.is_file()) {
+            return Some(error_response(
+                StatusCode::CONFLICT,
+                "already exists",
+                format!("{} is a file, not a folder", prefix),
+            ));
+        }
+    }
+    None
+}
+
+// Refuse creating, renaming or deleting a top-level folder the server reserves.
+fn reserved_folder(name: &str) -> Option<Response> {
+    if name == SCENARIOS_FOLDER {
+        return Some(error_response(
+            StatusCode::CONFLICT,
+            "reserved name",
+            format!("json/{} holds the scenario fixtures and can't be changed from the UI", name),
+        ));
+    }
+    RESERVED_FOLDERS.contains(&name).then(|| {
+        error_response(
+            StatusCode::CONFLICT,
+            "reserved name",
+            format!("/json/{} is a built-in route, so a folder named {} would be unreachable", name, name),
+        )
+    })
+}
+
+// Answer 404 for json/_scenarios, which is not a fixture folder of its own.
+fn scenarios_folder_hidden() -> Response {
+    error_response(
+        StatusCode::NOT_FOUND,
+        "not found",
+        format!("json/{} holds scenario fixtures and is not served directly", SCENARIOS_FOLDER),
+    )
+}
+
+// Top-level folder names taken by the /json form routes and the scenario fixtures.
+const RESERVED_FOLDERS: &[&str] = &[
+    "create",
+    "delete",
+    "rename",
+    "delete-file",
+    "move",
+    "copy",
+    SCENARIOS_FOLDER,
+];
+
+// Delete a subdirectory under json/ once confirm repeats its name.
+pub async fn delete_subdir(body: String) -> Response {
+    let name = form_value_utf8(&body, "name").unwrap_or_default();
+    if !is_safe_segment(&name) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe folder name", name),
+        );
+    }
+    if let Some(response) = reserved_folder(&name) {
+        return response;
+    }
+    if form_value_utf8(&body, "confirm").as_deref() != Some(name.as_str()) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "confirmation mismatch",
+            "confirm must repeat the folder name",
+        );
+    }
+
+    let dir = json_path(&name);
+    let result = fs::remove_dir_all(&dir).await;
+    bump_fs_generation();
+    if let Err(err) = result {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "delete failed",
+            format!("{}: {}", name, err),
+        );
+    }
+
+    Redirect::to("/json").into_response()
+}
+
+// Rename a subdirectory under json/ without clobbering an existing one.
+pub async fn rename_subdir(body: String) -> Response {
+    let from = form_value_utf8(&body, "from").unwrap_or_default();
+    let to = form_value_utf8(&body, "to").unwrap_or_default();
+    if !is_safe_segment(&from) || !is_safe_segment(&to) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            "from and to must be safe folder names",
+        );
+    }
+
+    if let Some(response) = reserved_folder(&from) {
+        return response;
+    }
+    if let Some(response) = folder_name_conflict(&to).await {
+        return response;
+    }
+    let from_dir = json_path(&from);
+    let to_dir = json_path(&to);
+    if fs::try_exists(&to_dir).await.unwrap_or(true) {
+        return error_response(
+            StatusCode::CONFLICT,
+            "already exists",
+            format!("{} already exists", to),
+        );
+    }
+    let result = fs::rename(from_dir, to_dir).await;
+    bump_fs_generation();
+    if let Err(err) = result {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "rename failed",
+            format!("{} -> {}: {}", from, to, err),
+        );
+    }
+
+    Redirect::to("/json").into_response()
+}
+
+// Delete a single fixture file under json/.
+pub async fn delete_file(body: String) -> Response {
+    let path = normalize_rel_path(&form_value_utf8(&body, "path").unwrap_or_default());
+    let path = path.trim_matches('/');
+    if path.is_empty() || !is_safe_rel_path(path) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            format!("{} is not a safe path under json/", path),
+        );
+    }
+
+    let target = json_path(path);
+    if !fs::symlink_metadata(&target).await.is_ok_and(|m| !m.is_dir()) {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", path),
+        );
+    }
+    let result = fs::remove_file(&target).await;
+    bump_fs_generation();
+    if let Err(err) = result {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "delete failed",
+            format!("{}: {}", path, err),
+        );
+    }
+
+    let folder = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(path);
+    Redirect::to(&format!("/json/{}", folder)).into_response()
+}
+
+// Return all route mappings as a JSON array.
+pub async fn get_routes() -> Response {
+    let mappings: Vec<serde_json::Value> =
+        read_route_mappings().iter().map(RouteMapping::to_json).collect();
+    json_response(StatusCode::OK, serde_json::Value::Array(mappings))
+}
+
+// List mappings that reference files missing from json/.
+pub async fn validate_routes() -> Response {
+    let mappings = read_route_mappings();
+    let dangling: Vec<serde_json::Value> = dangling_route_mappings(&mappings, &base_json_dir())
+        .into_iter()
+        .map(|(mapping, missing)| {
+            let mut value = mapping.to_json();
+            value["missing"] = serde_json::json!(missing);
+            value
+        })
+        .collect();
+    json_response(StatusCode::OK, serde_json::Value::Array(dangling))
+}
+
+// Lint a routes.txt body line by line without saving it.
+pub async fn check_routes(body: String) -> Response {
+    let base_dir = base_json_dir();
+    let mut seen: Vec<(String, String, usize)> = Vec::new();
+    let mut lines = Vec::new();
+    for (index, text) in body.lines().enumerate() {
+        let number = index + 1;
+        let mapping = match parse_route_line(text) {
+            Ok(None) => continue,
+            Ok(Some(mapping)) => mapping,
+            Err(reason) => {
+                lines.push(serde_json::json!({
+                    "line": number, "text": text.trim(), "valid": false, "reason": reason,
+                }));
+                continue;
+            }
+        };
+        let mut entry = serde_json::json!({ "line": number, "text": text.trim(), "valid": true });
+        if let Some(builtin) = shadowing_builtin(&mapping.method, &mapping.path) {
+            entry["valid"] = false.into();
+            entry["reason"] = format!(
+                "{} {} is the {} endpoint, which is answered before any mapping",
+                mapping.method, mapping.path, builtin
+            )
+            .into();
+        }
+        let mut warnings = Vec::new();
+        if let Some((_, _, first)) = seen
+            .iter()
+            .find(|(method, path, _)| *method == mapping.method && *path == mapping.path)
+        {
+            warnings.push(format!("duplicate of line {}, which wins", first));
+        } else {
+            seen.push((mapping.method.clone(), mapping.path.clone(), number));
+        }
+        for (_, missing) in dangling_route_mappings(std::slice::from_ref(&mapping), &base_dir) {
+            warnings.push(format!("{} not found in json/", missing.join(", ")));
+        }
+        if !warnings.is_empty() {
+            entry["warnings"] = serde_json::json!(warnings);
+        }
+        entry["mapping"] = mapping.to_json();
+        lines.push(entry);
+    }
+    let valid = lines.iter().all(|line| line["valid"] == true);
+    json_response(StatusCode::OK, serde_json::json!({ "valid": valid, "lines": lines }))
+}
+
+// Replace all route mappings from a JSON array; 400 lists the rejected entries.
+pub async fn set_routes(body: axum::body::Bytes) -> Response {
+    let Ok(serde_json::Value::Array(items)) = serde_json::from_slice::<serde_json::Value>(&body)
+    else {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            serde_json::json!({ "error": "invalid body", "detail": "expected a JSON array of mappings" }),
+        );
+    };
+
+    let mut mappings = Vec::new();
+    let mut invalid = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        match route_mapping_from_json(item).filter(|m| shadowing_builtin(&m.method, &m.path).is_none()) {
+            Some(mapping) => mappings.push(mapping),
+            None => invalid.push(serde_json::json!({ "index": index, "entry": item })),
+        }
+    }
+    if !invalid.is_empty() {
+        return json_response(
+            StatusCode::BAD_REQUEST,
+            serde_json::json!({
+                "error": "invalid mappings",
+                "detail": "some entries were rejected",
+                "invalid": invalid,
+            }),
+        );
+    }
+
+    if let Err(err) = write_route_mappings(&mappings) {
+        return config_write_failed("routes.txt", err);
+    }
+    get_routes().await
+}
+
+// Remove a single route mapping by method and path.
+pub async fn delete_route_mapping(body: String) -> Response {
+    let method = form_value(&body, "method").unwrap_or_default().trim().to_uppercase();
+    let path = form_value(&body, "path").unwrap_or_default().trim().to_string();
+
+    let mut mappings = read_route_mappings();
+    let before = mappings.len();
+    mappings.retain(|m| !(m.method == method && m.path == path));
+    if mappings.len() == before {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("no mapping for {} {}", method, path),
+        );
+    }
+    if let Err(err) = write_route_mappings(&mappings) {
+        return config_write_failed("routes.txt", err);
+    }
 
     Redirect::to("/json").into_response()
 }
 
+// Add or replace mappings from pasted routes.txt lines and report accepted/rejected ones.
+pub async fn set_routes_bulk(body: String) -> Response {
+    let routes = form_value_utf8(&body, "routes").unwrap_or_default();
+    let mut mappings = read_route_mappings();
+    let mut accepted = 0;
+    let mut rejected = Vec::new();
+    for (index, line) in routes.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let mapping = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(path), Some(file)) => build_route_mapping(method, path, file, parts)
+                .filter(|m| shadowing_builtin(&m.method, &m.path).is_none()),
+            _ => None,
+        };
+        match mapping {
+            Some(mapping) => {
+                mappings.retain(|m| !(m.method == mapping.method && m.path == mapping.path));
+                mappings.push(mapping);
+                accepted += 1;
+            }
+            None => rejected.push(serde_json::json!({ "line": index + 1, "text": line })),
+        }
+    }
+
+    if accepted > 0
+        && let Err(err) = write_route_mappings(&mappings)
+    {
+        return config_write_failed("routes.txt", err);
+    }
+    let status = if accepted > 0 || rejected.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    json_response(status, serde_json::json!({ "accepted": accepted, "rejected": rejected }))
+}
+
+// Move a fixture file to another path under json/.
+pub async fn move_file(body: String) -> Response {
+    transfer_file(&body, false).await
+}
+
+// Copy a fixture file to another path under json/.
+pub async fn copy_file(body: String) -> Response {
+    transfer_file(&body, true).await
+}
+
+// Shared move/copy logic: 404 on missing source, 409 on existing destination.
+async fn transfer_file(body: &str, copy: bool) -> Response {
+    let from = normalize_rel_path(&form_value_utf8(body, "from").unwrap_or_default());
+    let to = normalize_rel_path(&form_value_utf8(body, "to").unwrap_or_default());
+    let (from, to) = (from.trim_matches('/'), to.trim_matches('/'));
+    if from.is_empty() || to.is_empty() || !is_safe_rel_path(from) || !is_safe_rel_path(to) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid path",
+            "from and to must be safe paths under json/",
+        );
+    }
+
+    let from_path = json_path(from);
+    let to_path = json_path(to);
+    if !fs::metadata(&from_path).await.is_ok_and(|m| m.is_file()) {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", from),
+        );
+    }
+    if fs::try_exists(&to_path).await.unwrap_or(true) {
+        return error_response(
+            StatusCode::CONFLICT,
+            "already exists",
+            format!("{} already exists", to),
+        );
+    }
+    if let Some(parent) = to_path.parent()
+        && let Err(err) = fs::create_dir_all(parent).await
+    {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "create failed",
+            format!("{}: {}", to, err),
+        );
+    }
+    let result = if copy {
+        fs::copy(&from_path, &to_path).await.map(|_| ())
+    } else {
+        fs::rename(&from_path, &to_path).await
+    };
+    bump_fs_generation();
+    if let Err(err) = result {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "transfer failed",
+            format!("{} -> {}: {}", from, to, err),
+        );
+    }
+
+    let folder = to.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(to);
+    Redirect::to(&format!("/json/{}", folder)).into_response()
+}
+
+// Reject mutating /json and /config requests with 403 while read-only mode is on.
+pub async fn readonly_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    let path = request.uri().path();
+    let mutating = !matches!(
+        *request.method(),
+        axum::http::Method::GET | axum::http::Method::HEAD | axum::http::Method::OPTIONS
+    );
+    // The routes linter only reads its body, so it stays usable in read-only mode.
+    let guarded = (path == "/json" || path.starts_with("/json/") || path.starts_with("/config/"))
+        && path != "/config/routes/validate";
+    if mutating && guarded && read_readonly() {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "read-only mode",
+            "changes to fixtures and config are disabled",
+        );
+    }
+    next.run(request).await
+}
+
+// Answer requests to the echo endpoint with the request itself; others pass through.
+pub async fn echo_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    match read_echo_endpoint() {
+        Some(endpoint) if request.uri().path() == endpoint => echo_request(request).await,
+        _ => next.run(request).await,
+    }
+}
+
+// Reflect method, path, query, headers and body as JSON, masking redacted headers.
+async fn echo_request(request: axum::http::Request<Body>) -> Response {
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, read_max_body_bytes()).await else {
+        return body_too_large();
+    };
+    let redact = read_echo_redact();
+    let mut headers = serde_json::Map::new();
+    for name in parts.headers.keys() {
+        let values: Vec<serde_json::Value> = parts
+            .headers
+            .get_all(name)
+            .iter()
+            .map(|value| match redact.iter().any(|r| r == name.as_str()) {
+                true => "[redacted]".into(),
+                false => String::from_utf8_lossy(value.as_bytes()).into_owned().into(),
+            })
+            .collect();
+        let value = match <[_; 1]>::try_from(values) {
+            Ok([single]) => single,
+            Err(values) => serde_json::Value::Array(values),
+        };
+        headers.insert(name.as_str().to_string(), value);
+    }
+    let query = parts.uri.query().unwrap_or_default();
+    let mut echo = serde_json::json!({
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "raw_query": query,
+        "query": query_to_json(query),
+        "headers": headers,
+        "body": (!bytes.is_empty()).then(|| String::from_utf8_lossy(&bytes).into_owned()),
+    });
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+        echo["json"] = json;
+    }
+    let mut response = json_response(StatusCode::OK, echo);
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Report the build and the active setup as JSON for test harnesses.
+pub async fn version() -> Response {
+    json_response(StatusCode::OK, serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_hash": option_env!("STUB_GIT_HASH"),
+        "listen": listen_description(),
+        "json_dir": base_json_dir().display().to_string(),
+        "config_dir": base_config_dir().display().to_string(),
+        "readonly": read_readonly(),
+        "scenario": read_active_scenario(),
+    }))
+}
+
+// Reject request bodies over the global cap with 413; multipart uploads get their own cap.
+pub async fn body_limit_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    let upload = request.uri().path().starts_with("/json/")
+        && request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("multipart/form-data"));
+    let limit = match upload {
+        true => read_max_upload_request_bytes(),
+        false => read_max_request_bytes(),
+    };
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > limit) {
+        return error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload too large",
+            format!("request bodies are limited to {} bytes", limit),
+        );
+    }
+    // Bodies without a Content-Length are cut off while reading; extractors and uploads answer 413.
+    let (parts, body) = request.into_parts();
+    let body = Body::new(http_body_util::Limited::new(body, limit));
+    next.run(axum::http::Request::from_parts(parts, body)).await
+}
+
+// Sleep for the global delay before answering, unless the path is exempt.
+pub async fn global_delay_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    if let Some(range) = read_global_delay()
+        && !matches_path_pattern(read_global_delay_exempt(), request.uri().path())
+    {
+        tokio::time::sleep(pick_delay(range)).await;
+    }
+    next.run(request).await
+}
+
+// Search box hiding file list entries whose path doesn't contain the typed text.
+fn file_filter(lang: &str) -> String {
+    format!(
+        "<input type=\"text\" class=\"file-filter\" placeholder=\"{}\" oninput=\"var q=this.value.toLowerCase();this.parentNode.querySelectorAll('.file-list li').forEach(function(li){{var a=li.querySelector('a');li.style.display=!q||a.textContent.toLowerCase().includes(q)?'':'none';}})\">",
+        ui_text(lang, "search_files")
+    )
+}
+
+// Banner and style hiding the forms when read-only mode is on.
+fn readonly_notice(lang: &str) -> String {
+    if read_readonly() {
+        format!(
+            "<style>form{{display:none}}</style><p class=\"muted\" style=\"max-width:1000px;margin:0 auto 16px;padding:0 24px\">{}</p>",
+            ui_text(lang, "readonly")
+        )
+    } else {
+        String::new()
+    }
+}
+
+// Resolve the UI language for an HTML page from config or Accept-Language.
+fn page_lang(headers: &HeaderMap) -> &'static str {
+    ui_lang(headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()))
+}
+
+// Return 429 once a client IP exceeds the configured fixed-window rate limit.
+pub async fn rate_limit_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    let Some((max, window)) = read_rate_limit() else {
+        return next.run(request).await;
+    };
+    let Some(ConnectInfo(addr)) = request.extensions().get::<ConnectInfo<SocketAddr>>().cloned() else {
+        return next.run(request).await;
+    };
+
+    match check_rate_limit(addr.ip(), max, window) {
+        Ok(remaining) => {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+            response
+        }
+        Err(reset) => {
+            let mut response = error_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate limit exceeded",
+                format!("at most {} requests per {}s", max, window.as_secs()),
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(reset.as_secs().max(1)));
+            response
+                .headers_mut()
+                .insert("x-ratelimit-remaining", HeaderValue::from(0));
+            response
+        }
+    }
+}
+
+// Require a matching Authorization: Bearer token on /api/* when configured.
+pub async fn auth_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
+    let Some(token) = read_api_token() else {
+        return next.run(request).await;
+    };
+    // OPTIONS probes and preflights never carry credentials.
+    if request.method() == axum::http::Method::OPTIONS {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path();
+    let bypass = read_auth_bypass();
+    if (bypass.iter().any(|b| b == "ping") && read_ping_endpoint() == path)
+        || (bypass.iter().any(|b| b == "refresh") && read_refresh_endpoint() == path)
+    {
+        return next.run(request).await;
+    }
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim());
+    if provided == Some(token.as_str()) {
+        return next.run(request).await;
+    }
+
+    let (error, detail) = if provided.is_none() {
+        ("missing bearer token", "send Authorization: Bearer <token>")
+    } else {
+        ("invalid bearer token", "the token does not match config/api_token.txt")
+    };
+    let mut response = error_response(StatusCode::UNAUTHORIZED, error, detail);
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
 // Log requests and responses unless filtered.
 pub async fn log_middleware(request: axum::http::Request<Body>, next: Next) -> Response {
     let path = request.uri().path().to_string();
+    let method = request.method().to_string();
+    let uri = request.uri().to_string();
     let enabled = read_log_enabled();
     let ignored = is_log_ignored(&path);
+    let request_id = enabled.then(|| {
+        request
+            .headers()
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| is_valid_request_id(id))
+            .map(str::to_string)
+            .unwrap_or_else(new_request_id)
+    });
     if enabled && !ignored {
         tracing::info!(
-            method = %request.method(),
-            uri = %request.uri(),
+            method = %method,
+            uri = %uri,
+            request_id = request_id.as_deref().unwrap_or_default(),
             "request"
         );
-        log_line(format!("REQ {} {}", request.method(), request.uri()));
+        let mut entry = LogEntry::request(&method, &uri);
+        entry.request_id = request_id.clone();
+        log_line(entry);
     }
 
-    let response = next.run(request).await;
+    let started = std::time::Instant::now();
+    let mut response = next.run(request).await;
+    if let Some(id) = &request_id
+        && let Ok(value) = HeaderValue::from_str(id)
+    {
+        response.headers_mut().insert("x-request-id", value);
+    }
+    if log_json_enabled() {
+        tracing::info!(
+            target: "access",
+            method = %method,
+            path = %path,
+            status = response.status().as_u16(),
+            duration_ms = started.elapsed().as_secs_f64() * 1000.0,
+            "access"
+        );
+    }
     if enabled && !ignored {
         tracing::info!(
             status = %response.status(),
+            request_id = request_id.as_deref().unwrap_or_default(),
             "response"
         );
-        log_line(format!("RES {}", response.status()));
+        let mut entry = LogEntry::response(&method, &uri, response.status().as_u16());
+        entry.request_id = request_id;
+        if let Some(served) = response.extensions().get::<ServedFile>() {
+            entry.detail = Some(format!("{} ({} bytes)", served.file, served.bytes));
+        }
+        log_line(entry);
     }
     response
 }
 
-// Stream log lines to the browser via SSE.
-pub async fn sse_logs() -> Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>> {
+// Stream log lines to the browser via SSE, as JSON with ?format=json.
+pub async fn sse_logs(RawQuery(query): RawQuery, headers: HeaderMap) -> Response {
+    let json = form_value(&query.unwrap_or_default(), "format").as_deref() == Some("json");
+    // Subscribe before taking the snapshot so no line falls between replay and live.
     let receiver = subscribe_logs();
+    let replay: Vec<LogEntry> = match headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+    {
+        Some(last_id) => log_snapshot()
+            .into_iter()
+            .filter(|entry| entry.id > last_id)
+            .collect(),
+        None => Vec::new(),
+    };
+    let replayed = replay.last().map(|entry| entry.id).unwrap_or(0);
+    let replay = tokio_stream::iter(replay).map(move |entry| Ok(log_event(&entry, json)));
+    let live = BroadcastStream::new(receiver).filter_map(move |msg| match msg {
+        Ok(entry) if entry.id > replayed => Some(Ok(log_event(&entry, json))),
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(dropped)) => Some(Ok(lagged_event(dropped, json))),
+    });
+    dashboard_sse(replay.chain(live))
+}
+
+// Wrap a dashboard event stream with a reconnect hint and keep-alive comments.
+fn dashboard_sse<S>(stream: S) -> Response
+where
+    S: tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static,
+{
+    let retry = tokio_stream::once(Ok(Event::default().retry(SSE_RETRY)));
+    let sse = Sse::new(retry.chain(stream));
+    match read_sse_keepalive() {
+        Some(interval) => sse.keep_alive(KeepAlive::new().interval(interval)).into_response(),
+        None => sse.into_response(),
+    }
+}
+
+// Reconnect delay suggested to browsers through the SSE retry field.
+const SSE_RETRY: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Download the current log buffer as text, or JSON with ?format=json.
+pub async fn export_logs(RawQuery(query): RawQuery) -> Response {
+    let json = form_value(&query.unwrap_or_default(), "format").as_deref() == Some("json");
+    let lines: Vec<String> = log_snapshot().iter().map(|entry| entry.to_line()).collect();
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let (bytes, content_type, extension) = if json {
+        (
+            serde_json::to_vec(&lines).unwrap_or_default(),
+            "application/json",
+            "json",
+        )
+    } else {
+        let mut text = lines.join("\n");
+        text.push('\n');
+        (text.into_bytes(), "text/plain; charset=utf-8", "txt")
+    };
+
+    let mut response = Response::new(Body::from(bytes));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    let disposition = format!("attachment; filename=\"apifilestub-log-{}.{}\"", stamp, extension);
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        response.headers_mut().insert(header::CONTENT_DISPOSITION, value);
+    }
+    response
+}
+
+// Build an SSE event for a log entry, plain text or structured JSON.
+fn log_event(entry: &LogEntry, json: bool) -> Event {
+    let event = Event::default().id(entry.id.to_string());
+    if json {
+        let value = entry.to_json();
+        if let Ok(event) = event.clone().json_data(value) {
+            return event;
+        }
+    }
+    event.data(entry.to_line())
+}
+
+// Tell a slow subscriber how many log lines the broadcast channel dropped for it.
+fn lagged_event(dropped: u64, json: bool) -> Event {
+    let line = format!("... {} lines dropped ...", dropped);
+    if json {
+        let value = serde_json::json!({ "kind": "LAGGED", "dropped": dropped, "line": line });
+        if let Ok(event) = Event::default().json_data(value) {
+            return event;
+        }
+    }
+    Event::default().data(line)
+}
+
+// Stream fixture change events to the browser via SSE.
+pub async fn sse_fs_changes() -> Response {
+    let receiver = subscribe_fs_changes();
     let stream = BroadcastStream::new(receiver).filter_map(|msg| match msg {
-        Ok(line) => Some(Ok(Event::default().data(line))),
+        Ok(change) => Some(Ok(Event::default().data(format!(
+            "{} {} {}",
+            change.timestamp, change.kind, change.path
+        )))),
         Err(_) => None,
     });
-    Sse::new(stream)
+    dashboard_sse(stream)
+}
+
+// Serve a mapped fixture, adding the allowed CORS origin to whatever response comes out.
+async fn serve_mapped_json(mapping: &RouteMapping, query: &str, headers: &HeaderMap) -> Response {
+    let mut response = serve_mapped_fixture(mapping, query, headers).await;
+    set_cors_origin(&mut response, mapping, headers);
+    response
+}
+
+// Add Access-Control-Allow-Origin from the mapping's cors= option or config/cors.txt.
+fn set_cors_origin(response: &mut Response, mapping: &RouteMapping, headers: &HeaderMap) {
+    let allowlist = read_cors_origins();
+    if mapping.option("cors").is_none() && allowlist.is_empty() {
+        return;
+    }
+    let request_origin = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok());
+    let allowed = allowed_cors_origin(mapping.option("cors"), request_origin, &allowlist);
+    if let Some(origin) = allowed.and_then(|o| HeaderValue::from_str(&o).ok()) {
+        response
+            .headers_mut()
+            .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    }
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("Origin"));
 }
 
 // Read and return the mapped JSON response.
-async fn serve_mapped_json(file: &str) -> Response {
-    let path = base_json_dir().join(file);
-    match fs::read(path).await {
+async fn serve_mapped_fixture(mapping: &RouteMapping, query: &str, headers: &HeaderMap) -> Response {
+    if let Some(range) = mapping.option("delay").and_then(parse_delay_range) {
+        tokio::time::sleep(pick_delay(range)).await;
+    }
+    if mapping.option("mode") == Some("sse") {
+        return serve_mapped_sse(mapping).await;
+    }
+
+    let callback = match jsonp_callback(query) {
+        Ok(callback) => callback,
+        Err(status) => {
+            return error_response(
+                status,
+                "invalid callback",
+                "callback must be a dot-separated JavaScript identifier",
+            );
+        }
+    };
+    let negotiate = mapping.option("lang") == Some("negotiate");
+    let (file, language) = match negotiate {
+        true => negotiate_language(resolve_mapped_file(mapping), headers),
+        false => (resolve_mapped_file(mapping).to_string(), None),
+    };
+    let file = file.as_str();
+    let path = scenario_file_path(file);
+    match fs::read(&path).await {
         Ok(bytes) => {
-            let mut response = Response::new(Body::from(bytes));
+            let mut bytes = match convert_fixture(file, bytes) {
+                Ok(bytes) => bytes,
+                Err(error) => return conversion_error(error),
+            };
+            let plain_json = mapping
+                .option("content_type")
+                .is_none_or(|value| value == "application/json");
+            if callback.is_none() && plain_json && read_normalize_output() {
+                bytes = strip_bom_and_trailing(bytes);
+            }
+            let mut components_modified = None;
+            if mapping.option("mode") == Some("merge") {
+                (bytes, components_modified) = match merge_manifest(&bytes).await {
+                    Ok(merged) => merged,
+                    Err(error) => return conversion_error(error),
+                };
+            }
+            if let Some(strict) = read_env_substitution() {
+                bytes = match substitute_env(bytes, strict) {
+                    Ok(bytes) => bytes,
+                    Err(error) => return conversion_error(error),
+                };
+            }
+            if let Some(pointer) = mapping.option("pointer") {
+                bytes = match select_json_pointer(&bytes, pointer) {
+                    Ok(bytes) => bytes,
+                    Err(error) => return conversion_error(error),
+                };
+            }
+            if mapping.option("mode") == Some("ndjson")
+                && let Ok(serde_json::Value::Array(items)) = serde_json::from_slice(&bytes)
+            {
+                return serve_ndjson(mapping, items);
+            }
+            let bytes = match mapping.option("mode") {
+                Some("paginate") => paginate_mapped(mapping, query, bytes),
+                Some("schema") => fake_from_schema(query, bytes),
+                _ => bytes,
+            };
+            let bytes = wrap_envelope(bytes, file);
+            let bytes = format_json_output(bytes, &read_json_output());
+            let (mut bytes, content_type) = match &callback {
+                Some(callback) => (wrap_jsonp(bytes, callback), "application/javascript"),
+                None => (bytes, mapping.option("content_type").unwrap_or("application/json")),
+            };
+            let modified = file_modified(&path).await.max(components_modified);
+            let etag = compute_etag(&bytes, modified);
+            if let Some(response) = not_modified(headers, &etag, modified) {
+                return response;
+            }
+            let full_len = bytes.len();
+            let truncate = mapping
+                .option("unsafe_truncate")
+                .and_then(|v| v.parse::<usize>().ok());
+            if let Some(limit) = truncate {
+                bytes.truncate(limit);
+            }
+            let served_len = bytes.len();
+            let declare_full = truncate.is_some() && mapping.option("truncate_length") == Some("full");
+            let body = if declare_full {
+                // Unsized stream so hyper flushes the partial body before the length check fails.
+                let (tx, rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(1);
+                tokio::spawn(async move {
+                    let _ = tx.send(Ok(bytes)).await;
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                });
+                Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx))
+            } else {
+                Body::from(bytes)
+            };
+            let mut response = Response::new(body);
+            response.extensions_mut().insert(ServedFile {
+                file: file.to_string(),
+                bytes: served_len,
+            });
+            set_validators(&mut response, &etag, modified);
             response
                 .headers_mut()
-                .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                .insert(header::CONTENT_TYPE, content_type_value(content_type));
             response.headers_mut().insert(
                 header::CACHE_CONTROL,
                 HeaderValue::from_static("no-store"),
             );
+            if negotiate {
+                response
+                    .headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("Accept-Language"));
+            }
+            if let Some(language) = language.and_then(|l| HeaderValue::from_str(&l).ok()) {
+                response.headers_mut().insert(header::CONTENT_LANGUAGE, language);
+            }
+            if declare_full {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_LENGTH, HeaderValue::from(full_len));
+            }
             response
         }
-        Err(err) => match err.kind() {
-            std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND.into_response(),
-            _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-        },
+        Err(err) => fixture_read_failed(file, err),
+    }
+}
+
+// Pick the first .{lang} variant of a fixture matching Accept-Language, else the base file.
+fn negotiate_language(file: &str, headers: &HeaderMap) -> (String, Option<String>) {
+    let accepted = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(accept_languages)
+        .unwrap_or_default();
+    for language in accepted {
+        let variant = language_variant(file, &language);
+        if scenario_file_path(&variant).is_file() {
+            return (variant, Some(language));
+        }
+    }
+    (file.to_string(), None)
+}
+
+// Read a file's modification time, if available.
+async fn file_modified(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
+}
+
+// Return 304 Not Modified when If-None-Match (or, absent it, If-Modified-Since) is satisfied.
+fn not_modified(
+    headers: &HeaderMap,
+    etag: &str,
+    modified: Option<std::time::SystemTime>,
+) -> Option<Response> {
+    let fresh = if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        etag_matches(if_none_match.to_str().ok()?, etag)
+    } else {
+        let since = headers.get(header::IF_MODIFIED_SINCE)?.to_str().ok()?;
+        let since = httpdate::parse_http_date(since).ok()?;
+        unix_secs(modified?)? <= unix_secs(since)?
+    };
+    if !fresh {
+        return None;
+    }
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    set_validators(&mut response, etag, modified);
+    Some(response)
+}
+
+// Attach ETag and Last-Modified headers to a response.
+fn set_validators(response: &mut Response, etag: &str, modified: Option<std::time::SystemTime>) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    if let Some(modified) = modified
+        && let Ok(value) = HeaderValue::from_str(&httpdate::fmt_http_date(modified))
+    {
+        response.headers_mut().insert(header::LAST_MODIFIED, value);
+    }
+}
+
+// Whole seconds since the Unix epoch, for second-granularity date comparison.
+fn unix_secs(time: std::time::SystemTime) -> Option<u64> {
+    time.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+// Validate mapping fields the way the routing form does and build the mapping.
+fn build_route_mapping<'a>(
+    method: &str,
+    path: &str,
+    file: &str,
+    options: impl Iterator<Item = &'a str>,
+) -> Option<RouteMapping> {
+    let method = method.trim().to_uppercase();
+    if method != "GET" && method != "POST" {
+        return None;
+    }
+
+    let path = path.trim().to_string();
+    if !path.starts_with("/api/") || !is_safe_rel_path(path.trim_start_matches('/')) {
+        return None;
+    }
+
+    let file = normalize_json_file(file).ok()?;
+    if file.is_empty() || !is_safe_rel_path(&file) {
+        return None;
+    }
+
+    let options = parse_route_options(options)?;
+    Some(RouteMapping {
+        method,
+        path,
+        file,
+        options,
+    })
+}
+
+// Build a mapping from a config API object: {method, path, file, options?}.
+fn route_mapping_from_json(item: &serde_json::Value) -> Option<RouteMapping> {
+    let method = item.get("method")?.as_str()?;
+    let path = item.get("path")?.as_str()?;
+    let file = item.get("file")?.as_str()?;
+    let mut options = Vec::new();
+    match item.get("options") {
+        None | Some(serde_json::Value::Null) => {}
+        Some(serde_json::Value::Object(map)) => {
+            for (key, value) in map {
+                let value = match value {
+                    serde_json::Value::String(v) => v.clone(),
+                    serde_json::Value::Number(_) | serde_json::Value::Bool(_) => value.to_string(),
+                    _ => return None,
+                };
+                if value.is_empty() || value.contains(char::is_whitespace) {
+                    return None;
+                }
+                options.push(format!("{}={}", key, value));
+            }
+        }
+        Some(_) => return None,
     }
+    build_route_mapping(method, path, file, options.iter().map(String::as_str))
+}
+
+// Build a {"error", "detail"} JSON body with the given status.
+fn error_response(status: StatusCode, error: &str, detail: impl Into<String>) -> Response {
+    json_response(status, serde_json::json!({ "error": error, "detail": detail.into() }))
+}
+
+// Answer 500 when a config/ file could not be written.
+fn config_write_failed(name: &str, err: std::io::Error) -> Response {
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "write failed",
+        format!("config/{}: {}", name, err),
+    )
+}
+
+// Answer 404 for a missing mapped fixture and 500 for any other read error.
+fn fixture_read_failed(file: &str, err: std::io::Error) -> Response {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => error_response(
+            StatusCode::NOT_FOUND,
+            "not found",
+            format!("{} does not exist", file),
+        ),
+        _ => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "read failed",
+            format!("{}: {}", file, err),
+        ),
+    }
+}
+
+// Answer 413 for a request body over the per-request read limit.
+fn body_too_large() -> Response {
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload too large",
+        format!("request bodies are limited to {} bytes", read_max_body_bytes()),
+    )
+}
+
+// Build a JSON response with the given status.
+fn json_response(status: StatusCode, value: serde_json::Value) -> Response {
+    let mut response = Response::new(Body::from(value.to_string()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
 }
 
 // Normalize a JSON file path relative to json/.
-fn normalize_json_file(input: &str) -> Result<String, Response> {
+fn normalize_json_file(input: &str) -> Result<String, StatusCode> {
     let mut trimmed = input.trim().to_string();
     if trimmed.starts_with("/json/") {
         trimmed = trimmed.trim_start_matches("/json/").to_string();
@@ -735,15 +3543,538 @@ fn normalize_json_file(input: &str) -> Result<String, Response> {
         trimmed = trimmed.trim_start_matches("json/").to_string();
     }
     if trimmed.starts_with('/') {
-        return Err(StatusCode::BAD_REQUEST.into_response());
+        return Err(StatusCode::BAD_REQUEST);
     }
     Ok(trimmed)
 }
 
-// Lookup a mapping for the given method and path.
-fn find_route_mapping(method: &str, path: &str) -> Option<String> {
-    read_route_mappings()
+// Apply the mapping's pagination settings to an array fixture.
+fn paginate_mapped(mapping: &RouteMapping, query: &str, bytes: Vec<u8>) -> Vec<u8> {
+    let page_param = mapping.option("page_param").unwrap_or("page");
+    let per_page_param = mapping.option("per_page_param").unwrap_or("per_page");
+    let default_per_page = mapping
+        .option("per_page")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10);
+    let page = form_value(query, page_param)
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1);
+    let per_page = form_value(query, per_page_param)
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default_per_page);
+    let data_key = mapping.option("data_key").unwrap_or("data");
+    paginate_json(&bytes, page, per_page, data_key).unwrap_or(bytes)
+}
+
+// Replace a JSON Schema fixture with generated data, seeded by ?seed= when present.
+fn fake_from_schema(query: &str, bytes: Vec<u8>) -> Vec<u8> {
+    let Ok(schema) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return bytes;
+    };
+    let mut rng = FakeRng::new(form_value(query, "seed").map(|v| parse_seed(&v)));
+    match generate_from_schema(&schema, &mut rng) {
+        Some(value) => value.to_string().into_bytes(),
+        None => bytes,
+    }
+}
+
+// Read a mapping's fixture and stream it as SSE events.
+async fn serve_mapped_sse(mapping: &RouteMapping) -> Response {
+    let file = resolve_mapped_file(mapping);
+    match fs::read(scenario_file_path(file)).await {
+        Ok(bytes) => stream_sse_events(mapping, &bytes),
+        Err(err) => fixture_read_failed(file, err),
+    }
+}
+
+// Stream a fixture's entries as SSE events at the mapping's interval, looping when asked.
+fn stream_sse_events(mapping: &RouteMapping, bytes: &[u8]) -> Response {
+    let events = parse_sse_events(bytes);
+    let interval = mapping_interval(mapping);
+    let looping = mapping.option("loop").is_some_and(is_enabled_value) && !events.is_empty();
+    let items: Box<dyn Iterator<Item = String> + Send> = if looping {
+        Box::new(events.into_iter().cycle())
+    } else {
+        Box::new(events.into_iter())
+    };
+    let stream = tokio_stream::iter(items)
+        .map(|data| Ok::<_, std::convert::Infallible>(Event::default().data(data)))
+        .throttle(interval);
+    Sse::new(stream).into_response()
+}
+
+// Stream array elements as newline-delimited JSON, one chunk per element.
+fn serve_ndjson(mapping: &RouteMapping, items: Vec<serde_json::Value>) -> Response {
+    let delay = mapping
+        .option("interval")
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(std::time::Duration::from_millis)
+        .unwrap_or_default();
+    let lines = items.into_iter().map(|item| {
+        let mut line = item.to_string();
+        line.push('\n');
+        Ok::<_, std::convert::Infallible>(line)
+    });
+    let stream = tokio_stream::iter(lines).throttle(delay);
+
+    let mut response = Response::new(Body::from_stream(stream));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+// Delay between streamed messages from the mapping's interval option (default 1000ms).
+fn mapping_interval(mapping: &RouteMapping) -> std::time::Duration {
+    let millis = mapping
+        .option("interval")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(1000);
+    std::time::Duration::from_millis(millis)
+}
+
+// Send the fixture messages, then echo whatever the client sends until it closes.
+async fn ws_session(mut socket: WebSocket, messages: Vec<String>, interval: std::time::Duration) {
+    for (index, message) in messages.into_iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(interval).await;
+        }
+        if socket.send(Message::Text(message)).await.is_err() {
+            return;
+        }
+    }
+    while let Some(Ok(message)) = socket.recv().await {
+        let echo = matches!(message, Message::Text(_) | Message::Binary(_));
+        if matches!(message, Message::Close(_)) || (echo && socket.send(message).await.is_err()) {
+            return;
+        }
+    }
+}
+
+// Pick the file to serve, honoring an optional time-of-day schedule.
+fn resolve_mapped_file(mapping: &RouteMapping) -> &str {
+    if let (Some(spec), Some(file)) = (mapping.option("schedule"), mapping.option("schedule_file")) {
+        let tz = mapping.option("tz").unwrap_or("UTC");
+        if is_schedule_active(spec, tz) {
+            return file;
+        }
+    }
+    &mapping.file
+}
+
+// Lookup a mapping for the given method and path, preferring the active scenario.
+fn find_route_mapping(method: &str, path: &str) -> Option<RouteMapping> {
+    let base = read_route_mappings()
         .into_iter()
-        .find(|m| m.method == method && m.path == path)
-        .map(|m| m.file)
+        .find(|m| m.method == method && m.path == path);
+    let Some(scenario) = read_active_scenario() else {
+        return base;
+    };
+    let Some(over) = read_scenario_overrides()
+        .into_iter()
+        .find(|o| o.scenario == scenario && o.method == method && o.path == path)
+    else {
+        return base;
+    };
+    let mut mapping = base.unwrap_or(RouteMapping {
+        method: over.method,
+        path: over.path,
+        file: String::new(),
+        options: Vec::new(),
+    });
+    mapping.file = over.file;
+    Some(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::tests::use_temp_root;
+
+    // Split a handler response into its status and parsed JSON body (Null when not JSON).
+    async fn json_body(response: Response) -> (StatusCode, serde_json::Value) {
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    // Request headers built from (name, value) pairs.
+    fn header_map(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn not_modified_on_etag_match_only() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        let modified = Some(time);
+        let etag = compute_etag(b"{}", modified);
+
+        let matching = header_map(&[(header::IF_NONE_MATCH, &etag)]);
+        let hit = not_modified(&matching, &etag, modified).expect("matching ETag answers 304");
+        assert_eq!(hit.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(hit.headers().get(header::ETAG).unwrap(), etag.as_str());
+
+        let other = header_map(&[(header::IF_NONE_MATCH, "\"other\"")]);
+        assert!(not_modified(&other, &etag, modified).is_none());
+        assert!(not_modified(&HeaderMap::new(), &etag, modified).is_none());
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence_over_if_modified_since() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        let modified = Some(time);
+        let etag = compute_etag(b"{}", modified);
+        let later = httpdate::fmt_http_date(time + std::time::Duration::from_secs(60));
+        let earlier = httpdate::fmt_http_date(time - std::time::Duration::from_secs(60));
+
+        let since_later = header_map(&[(header::IF_MODIFIED_SINCE, &later)]);
+        assert!(not_modified(&since_later, &etag, modified).is_some());
+        let since_earlier = header_map(&[(header::IF_MODIFIED_SINCE, &earlier)]);
+        assert!(not_modified(&since_earlier, &etag, modified).is_none());
+
+        // A stale ETag wins over a date that alone would answer 304.
+        let stale = header_map(&[
+            (header::IF_NONE_MATCH, "\"other\""),
+            (header::IF_MODIFIED_SINCE, &later),
+        ]);
+        assert!(not_modified(&stale, &etag, modified).is_none());
+        // A matching ETag wins over a date that alone would answer 200.
+        let fresh = header_map(&[
+            (header::IF_NONE_MATCH, &etag),
+            (header::IF_MODIFIED_SINCE, &earlier),
+        ]);
+        assert!(not_modified(&fresh, &etag, modified).is_some());
+    }
+
+    #[tokio::test]
+    async fn delete_subdir_requires_confirmation() {
+        let root = use_temp_root();
+        std::fs::create_dir_all(root.join("json/users")).unwrap();
+
+        let (status, body) = json_body(delete_subdir("name=users".to_string()).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "confirmation mismatch");
+        let (status, _) = json_body(delete_subdir("name=users&confirm=user".to_string()).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(root.join("json/users").is_dir());
+
+        let response = delete_subdir("name=users&confirm=users".to_string()).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(!root.join("json/users").exists());
+    }
+
+    #[tokio::test]
+    async fn rename_subdir_refuses_existing_targets() {
+        let root = use_temp_root();
+        std::fs::create_dir_all(root.join("json/old")).unwrap();
+        std::fs::create_dir_all(root.join("json/taken")).unwrap();
+        std::fs::write(root.join("json/file.json"), "{}").unwrap();
+
+        for to in ["taken", "file.json", "delete"] {
+            let body = format!("from=old&to={}", to);
+            let (status, _) = json_body(rename_subdir(body).await).await;
+            assert_eq!(status, StatusCode::CONFLICT, "renaming onto {}", to);
+        }
+        assert!(root.join("json/old").is_dir());
+
+        let response = rename_subdir("from=old&to=new".to_string()).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(root.join("json/new").is_dir() && !root.join("json/old").exists());
+    }
+
+    #[tokio::test]
+    async fn wrong_method_is_405_with_allow_and_unknown_path_is_404() {
+        let root = use_temp_root();
+        std::fs::write(root.join("json/items.json"), "[]").unwrap();
+        write_config_file("routes.txt", "POST /api/items items.json\n").unwrap();
+
+        let get = |path: &str| api_get(Path(path.to_string()), RawQuery(None), HeaderMap::new());
+        let response = get("items").await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers().get(header::ALLOW).unwrap(), "POST");
+
+        let post = api_post(Path("v1/ping".to_string()), RawQuery(None), HeaderMap::new(), Body::empty());
+        let response = post.await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers().get(header::ALLOW).unwrap(), "GET");
+
+        let response = get("unknown").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::ALLOW).is_none());
+
+        let response = get("v1/ping").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn upstream_headers_drop_hop_by_hop_and_stub_token() {
+        let headers = header_map(&[
+            (header::HOST, "localhost:3000"),
+            (header::CONTENT_LENGTH, "12"),
+            (header::TRANSFER_ENCODING, "chunked"),
+            (header::CONNECTION, "keep-alive, X-Trace"),
+            (header::TE, "trailers"),
+            (header::UPGRADE, "websocket"),
+            (header::PROXY_AUTHORIZATION, "Basic Zm9v"),
+            (header::HeaderName::from_static("x-trace"), "1"),
+            (header::ACCEPT, "application/json"),
+            (header::AUTHORIZATION, "Bearer stub-secret"),
+        ]);
+        let forwarded = upstream_headers(&headers, Some("stub-secret"));
+        let names: Vec<&str> = forwarded.keys().map(|name| name.as_str()).collect();
+        assert_eq!(names, ["accept"]);
+
+        // A token meant for the upstream itself still goes through.
+        let forwarded = upstream_headers(&headers, Some("other"));
+        assert_eq!(forwarded.get(header::AUTHORIZATION).unwrap(), "Bearer stub-secret");
+        assert!(upstream_headers(&headers, None).contains_key(header::AUTHORIZATION));
+    }
+
+    #[tokio::test]
+    async fn form_writes_invalidate_the_cached_dashboard() {
+        let _root = use_temp_root();
+        let ttl = std::time::Duration::from_secs(60);
+
+        store_dashboard("it", fs_generation(), "stale".to_string());
+        let response = create_subdir("name=orders".to_string()).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(cached_dashboard("it", ttl).is_none());
+
+        store_dashboard("it", fs_generation(), "stale".to_string());
+        write_config_file("ping_endpoint.txt", "/api/v2/ping").unwrap();
+        assert!(cached_dashboard("it", ttl).is_none());
+
+        store_dashboard("it", fs_generation(), "stale".to_string());
+        write_atomic_async(json_path("orders/list.json"), b"[]".to_vec()).await.unwrap();
+        assert!(cached_dashboard("it", ttl).is_none());
+    }
+
+    // Multipart extractor over a body streamed in the given chunks and cut at limit bytes.
+    async fn multipart_from_chunks(chunks: Vec<String>, limit: usize) -> Multipart {
+        use axum::extract::FromRequest;
+        let stream = tokio_stream::iter(chunks.into_iter().map(Ok::<_, std::io::Error>));
+        let body = Body::new(http_body_util::Limited::new(Body::from_stream(stream), limit));
+        let request = axum::http::Request::builder()
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XX")
+            .body(body)
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn upload_cut_at_the_cap_saves_nothing() {
+        let root = use_temp_root();
+        let first = "--XX\r\nContent-Disposition: form-data; name=\"files\"; filename=\"a.json\"\r\n\r\n{}\r\n";
+        let second = "--XX\r\nContent-Disposition: form-data; name=\"files\"; filename=\"b.json\"\r\n\r\n[";
+        let mut chunks = vec![first.to_string(), second.to_string()];
+        chunks.extend((0..16).map(|_| "0,".repeat(64)));
+        chunks.push("0]\r\n--XX--\r\n".to_string());
+
+        let multipart = multipart_from_chunks(chunks, first.len() + second.len() + 256).await;
+        let response = upload_files(Path("docs".to_string()), RawQuery(None), HeaderMap::new(), multipart).await;
+        let (status, body) = json_body(response).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "payload too large");
+        assert!(!root.join("json/docs").exists());
+
+        let chunks = vec![first.to_string(), "--XX--\r\n".to_string()];
+        let multipart = multipart_from_chunks(chunks, 4096).await;
+        let response = upload_files(Path("docs".to_string()), RawQuery(None), HeaderMap::new(), multipart).await;
+        assert!(response.status().is_redirection() || response.status().is_success());
+        assert!(root.join("json/docs/a.json").exists());
+    }
+
+    #[tokio::test]
+    async fn stub_errors_share_the_json_error_shape() {
+        let _root = use_temp_root();
+        write_config_file("routes.txt", "GET /api/feed missing.json mode=sse\n").unwrap();
+
+        for response in [
+            sse_stub(Path("unmapped".to_string())).await,
+            sse_stub(Path("feed".to_string())).await,
+            sse_stub(Path("../etc".to_string())).await,
+            conversion_error("pointer /x matches nothing in the fixture".to_string()),
+            body_too_large(),
+        ] {
+            assert_eq!(response.headers().get(header::CACHE_CONTROL).unwrap(), "no-store");
+            let (status, body) = json_body(response).await;
+            assert!(status.is_client_error() || status.is_server_error());
+            assert!(body["error"].is_string(), "{}", body);
+            assert!(body["detail"].is_string(), "{}", body);
+        }
+        let (status, body) = json_body(sse_stub(Path("feed".to_string())).await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["detail"], "missing.json does not exist");
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_skips_recording() {
+        let root = use_temp_root();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream = format!("http://{}", listener.local_addr().unwrap());
+        let app = axum::Router::new().route("/api/users", axum::routing::get(|| async { "[]" }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        write_config_file("routes.txt", "").unwrap();
+        write_config_file("record.txt", "on").unwrap();
+
+        write_config_file("readonly.txt", "on").unwrap();
+        let response = proxy_upstream(&upstream, "GET", "/api/users", "", &HeaderMap::new(), Vec::new()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(read_route_mappings().is_empty());
+        assert!(!root.join("json/recorded").exists());
+
+        write_config_file("readonly.txt", "off").unwrap();
+        proxy_upstream(&upstream, "GET", "/api/users", "", &HeaderMap::new(), Vec::new()).await;
+        assert_eq!(read_route_mappings().len(), 1);
+        assert!(root.join("json/recorded/users.json").exists());
+    }
+
+    #[tokio::test]
+    async fn auth_gate_bypass_missing_and_wrong_token() {
+        let _root = use_temp_root();
+        write_config_file("api_token.txt", "secret\n").unwrap();
+        write_config_file("auth_bypass.txt", "ping").unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let app = axum::Router::new().route(
+            "/api/*path",
+            axum::routing::any(|| async { "ok" }).layer(axum::middleware::from_fn(auth_middleware)),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = reqwest::Client::new();
+        let send = |method: reqwest::Method, path: &str, token: Option<&str>| {
+            let mut request = client.request(method, format!("{}{}", base, path));
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, token);
+            }
+            request.send()
+        };
+
+        let missing = send(reqwest::Method::GET, "/api/users", None).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(missing.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let body: serde_json::Value = serde_json::from_slice(&missing.bytes().await.unwrap()).unwrap();
+        assert_eq!(body["error"], "missing bearer token");
+
+        let wrong = send(reqwest::Method::GET, "/api/users", Some("Bearer nope")).await.unwrap();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value = serde_json::from_slice(&wrong.bytes().await.unwrap()).unwrap();
+        assert_eq!(body["error"], "invalid bearer token");
+        let basic = send(reqwest::Method::GET, "/api/users", Some("Basic secret")).await.unwrap();
+        assert_eq!(basic.status(), StatusCode::UNAUTHORIZED);
+
+        let ok = send(reqwest::Method::GET, "/api/users", Some("Bearer secret")).await.unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+        let ping = send(reqwest::Method::GET, "/api/v1/ping", None).await.unwrap();
+        assert_eq!(ping.status(), StatusCode::OK);
+        let refresh = send(reqwest::Method::POST, "/api/v1/authentication/refresh", None).await.unwrap();
+        assert_eq!(refresh.status(), StatusCode::UNAUTHORIZED);
+        let preflight = send(reqwest::Method::OPTIONS, "/api/users", None).await.unwrap();
+        assert_eq!(preflight.status(), StatusCode::OK);
+
+        write_config_file("api_token.txt", "").unwrap();
+        let open = send(reqwest::Method::GET, "/api/users", None).await.unwrap();
+        assert_eq!(open.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn scenario_overrides_win_over_base_mappings() {
+        let _root = use_temp_root();
+        write_config_file(
+            "routes.txt",
+            "GET /api/users users.json delay=50\nPOST /api/users created.json\n",
+        )
+        .unwrap();
+        write_config_file(
+            "scenarios.txt",
+            "degraded GET /api/users users-empty.json\n\
+             degraded GET /api/status down.json\n\
+             maintenance GET /api/users offline.json\n",
+        )
+        .unwrap();
+        let file = |method: &str, path: &str| find_route_mapping(method, path).map(|m| m.file);
+
+        write_config_file("scenario.txt", "").unwrap();
+        assert_eq!(file("GET", "/api/users").as_deref(), Some("users.json"));
+        assert_eq!(file("GET", "/api/status"), None);
+
+        write_config_file("scenario.txt", "degraded\n").unwrap();
+        let users = find_route_mapping("GET", "/api/users").unwrap();
+        assert_eq!(users.file, "users-empty.json");
+        // The override swaps only the file; the base mapping's options stay.
+        assert_eq!(users.option("delay"), Some("50"));
+        assert_eq!(file("POST", "/api/users").as_deref(), Some("created.json"));
+        assert_eq!(file("GET", "/api/status").as_deref(), Some("down.json"));
+
+        write_config_file("scenario.txt", "unknown").unwrap();
+        assert_eq!(file("GET", "/api/users").as_deref(), Some("users.json"));
+    }
+
+    #[tokio::test]
+    async fn stub_fixture_checks_both_paths() {
+        let root = use_temp_root();
+        std::fs::create_dir_all(root.join("json")).unwrap();
+        std::fs::write(root.join("json/feed.json"), "[1, 2]").unwrap();
+        write_config_file(
+            "routes.txt",
+            "GET /api/feed feed.json\nGET /api/escape ../config/routes.txt\nGET /api/gone gone.json\n",
+        )
+        .unwrap();
+        let status = |found: Result<(RouteMapping, Vec<u8>), Response>| match found {
+            Ok(_) => StatusCode::OK,
+            Err(response) => response.status(),
+        };
+
+        let (mapping, bytes) = stub_fixture("feed").await.ok().unwrap();
+        assert_eq!(mapping.file, "feed.json");
+        assert_eq!(bytes, b"[1, 2]");
+        assert_eq!(status(stub_fixture("../feed").await), StatusCode::BAD_REQUEST);
+        assert_eq!(status(stub_fixture("").await), StatusCode::BAD_REQUEST);
+        // The route parser drops the escaping line, so there is no mapping to follow.
+        assert_eq!(status(stub_fixture("escape").await), StatusCode::NOT_FOUND);
+        assert_eq!(status(stub_fixture("missing").await), StatusCode::NOT_FOUND);
+        assert_eq!(status(stub_fixture("gone").await), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn scenarios_folder_is_reserved_and_hidden() {
+        let root = use_temp_root();
+        std::fs::create_dir_all(root.join("json/_scenarios/error")).unwrap();
+        std::fs::write(root.join("json/_scenarios/error/users.json"), "[]").unwrap();
+        std::fs::create_dir_all(root.join("json/users")).unwrap();
+        std::fs::write(root.join("json/users/all.json"), "[]").unwrap();
+
+        let (entries, subdirs, _) = collect_json_index(root.join("json"), 8, 100);
+        assert_eq!(subdirs, ["users"]);
+        assert!(entries.iter().all(|entry| !entry.path.starts_with("_scenarios")));
+
+        for response in [
+            delete_subdir("name=_scenarios&confirm=_scenarios".to_string()).await,
+            rename_subdir("from=_scenarios&to=old".to_string()).await,
+            rename_subdir("from=users&to=_scenarios".to_string()).await,
+            create_subdir("name=_scenarios%2Fnew".to_string()).await,
+        ] {
+            let (status, body) = json_body(response).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+            assert_eq!(body["error"], "reserved name");
+        }
+        assert!(root.join("json/_scenarios/error/users.json").exists());
+
+        let raw = get_json(
+            Path(("_scenarios".to_string(), "error/users.json".to_string())),
+            RawQuery(None),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(raw.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -7,10 +7,21 @@ use tracing::Level;
 // App entry point: init logging, filesystem watch, and HTTP router.
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .init();
+    if tools::log_json_enabled() {
+        tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_max_level(Level::INFO)
+            .init();
+    } else {
+        tracing_subscriber::fmt()
+            .with_max_level(Level::INFO)
+            .init();
+    }
 
+    if let Some(root) = tools::root_dir_from_env() {
+        tools::set_root_dir(root);
+    }
     tools::init_log_state();
     tools::start_fs_watch();
 
@@ -18,23 +29,141 @@ async fn main() {
         .route("/", get(api::index))
         .route("/json", get(api::index))
         .route("/json/", get(api::index))
+        .route("/json.json", get(api::json_index))
         .route("/events", get(api::sse_logs))
+        .route("/events/fs", get(api::sse_fs_changes))
+        .route("/events/export", get(api::export_logs))
+        .route("/version", get(api::version))
         .route("/json/create", axum::routing::post(api::create_subdir))
         .route("/json/delete", axum::routing::post(api::delete_subdir))
         .route("/json/rename", axum::routing::post(api::rename_subdir))
-        .route("/json/:subdir", get(api::subdir_index).post(api::upload_files))
-        .route("/json/:subdir/*path", get(api::get_json))
+        .route("/json/delete-file", axum::routing::post(api::delete_file))
+        .route("/json/move", axum::routing::post(api::move_file))
+        .route("/json/copy", axum::routing::post(api::copy_file))
+        .route(
+            "/json/:subdir",
+            get(api::subdir_index).post(api::upload_files),
+        )
+        .route("/json/:subdir/", get(api::subdir_index))
+        .route(
+            "/json/:subdir/*path",
+            get(api::get_json).put(api::save_json).post(api::save_json),
+        )
         .route("/config/refresh-endpoint", post(api::set_refresh_endpoint))
         .route("/config/ping-endpoint", post(api::set_ping_endpoint))
+        .route("/config/routes", get(api::get_routes).post(api::set_routes))
+        .route(
+            "/config/routes/validate",
+            get(api::validate_routes).post(api::check_routes),
+        )
+        .route("/config/assertions", get(api::get_assertions))
+        .route("/config/routes-bulk", post(api::set_routes_bulk))
         .route("/config/route-mapping", post(api::set_route_mapping))
+        .route("/config/route-delete", post(api::delete_route_mapping))
         .route("/config/log-ignore", post(api::set_log_ignore))
         .route("/config/log-toggle", post(api::set_log_toggle))
-        .route("/api/*path", get(api::api_get).post(api::api_post))
+        .route("/config/scenario", post(api::set_scenario))
+        .route("/config/import-openapi", post(api::import_openapi))
+        .route("/config/export-openapi", get(api::export_openapi))
+        .route("/config/export-postman", get(api::export_postman))
+        .route(
+            "/api/*path",
+            get(api::api_get)
+                .post(api::api_post)
+                .options(api::api_options)
+                .layer(middleware::from_fn(api::echo_middleware))
+                .layer(middleware::from_fn(api::auth_middleware))
+                .layer(middleware::from_fn(api::rate_limit_middleware)),
+        )
+        .route(
+            "/graphql",
+            post(api::graphql)
+                .layer(middleware::from_fn(api::auth_middleware))
+                .layer(middleware::from_fn(api::rate_limit_middleware)),
+        )
+        .route("/sse/*path", get(api::sse_stub))
+        .route("/ws/*path", get(api::ws_stub))
+        .layer(middleware::from_fn(api::global_delay_middleware))
+        .layer(middleware::from_fn(api::body_limit_middleware))
+        .layer(axum::extract::DefaultBodyLimit::disable())
+        .layer(middleware::from_fn(api::readonly_middleware))
         .layer(middleware::from_fn(api::log_middleware));
 
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
-        .await
-        .expect("failed to bind");
-    println!("Listening on http://127.0.0.1:3000");
-    axum::serve(listener, app).await.expect("server error");
+    #[cfg(unix)]
+    if let Some(path) = tools::unix_socket_path() {
+        serve_unix(app, &path).await;
+        return;
+    }
+    #[cfg(not(unix))]
+    if tools::unix_socket_path().is_some() {
+        tracing::warn!("STUB_UNIX_SOCKET is ignored on this platform, listening on TCP");
+    }
+
+    let mut servers = tokio::task::JoinSet::new();
+    if let Some((cert, key)) = tools::tls_paths() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(cert, key)
+            .await
+            .expect("failed to load TLS certificate or key");
+        for addr in tools::bind_addresses() {
+            let socket_addr: std::net::SocketAddr = addr.parse().expect("invalid bind address");
+            println!("Listening on https://{}", addr);
+            let server = axum_server::bind_rustls(socket_addr, config.clone())
+                .serve(app.clone().into_make_service_with_connect_info::<std::net::SocketAddr>());
+            servers.spawn(server);
+        }
+    } else {
+        for addr in tools::bind_addresses() {
+            // axum::serve auto-detects HTTP/1.1 and h2c prior-knowledge connections.
+            let listener = tokio::net::TcpListener::bind(&addr)
+                .await
+                .expect("failed to bind");
+            println!("Listening on http://{}", addr);
+            let server = axum::serve(
+                listener,
+                app.clone().into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            );
+            servers.spawn(server.into_future());
+        }
+    }
+    // Every listener runs until the process stops; the first failure takes the server down.
+    while let Some(result) = servers.join_next().await {
+        result.expect("server task panicked").expect("server error");
+    }
+}
+
+// Serve the router on a Unix domain socket, replacing a stale socket file.
+#[cfg(unix)]
+async fn serve_unix(app: Router, path: &std::path::Path) {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::service::TowerToHyperService;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| {
+        use std::os::unix::fs::FileTypeExt;
+        meta.file_type().is_socket()
+    }) {
+        let _ = std::fs::remove_file(path);
+    }
+    let listener = tokio::net::UnixListener::bind(path).expect("failed to bind unix socket");
+    println!("Listening on unix:{}", path.display());
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                tracing::warn!(error = %err, "unix socket accept failed");
+                continue;
+            }
+        };
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            // Same HTTP/1.1 + h2c auto-detection as the TCP listener, with upgrades for /ws.
+            if let Err(err) = Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(error = %err, "unix socket connection closed");
+            }
+        });
+    }
 }
@@ -1,8 +1,13 @@
 use notify::Watcher;
 use std::{
-    collections::VecDeque,
-    path::PathBuf,
-    sync::{Mutex, OnceLock},
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock, RwLock,
+    },
+    time::{Duration, Instant},
 };
 use tokio::sync::broadcast;
 
@@ -12,29 +17,483 @@ pub struct RouteMapping {
     pub method: String,
     pub path: String,
     pub file: String,
+    pub options: Vec<(String, String)>,
+}
+
+impl RouteMapping {
+    // Lookup an optional key=value setting attached to the mapping.
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Files under json/ the mapping may serve: the main file plus file-valued options.
+    pub fn referenced_files(&self) -> Vec<&str> {
+        let mut files = vec![self.file.as_str()];
+        files.extend(
+            self.options
+                .iter()
+                .filter(|(k, _)| FILE_OPTIONS.contains(&k.as_str()))
+                .map(|(_, v)| v.as_str()),
+        );
+        files
+    }
+
+    // Render as a JSON object for the config API.
+    pub fn to_json(&self) -> serde_json::Value {
+        let options: serde_json::Map<String, serde_json::Value> = self
+            .options
+            .iter()
+            .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+            .collect();
+        serde_json::json!({
+            "method": self.method,
+            "path": self.path,
+            "file": self.file,
+            "options": options,
+        })
+    }
+}
+
+// Mapping options whose value is a file path relative to json/.
+const FILE_OPTIONS: &[&str] = &["schedule_file", "max_body_file", "expect"];
+
+// Response modes a mapping can select with mode=<name>.
+const RESPONSE_MODES: &[&str] = &["json", "sse", "paginate", "ndjson", "schema", "merge"];
+
+// Scenario override entry stored in config/scenarios.txt.
+#[derive(Clone)]
+pub struct ScenarioOverride {
+    pub scenario: String,
+    pub method: String,
+    pub path: String,
+    pub file: String,
+}
+
+// Structured log entry kept in the buffer and broadcast over SSE.
+#[derive(Clone)]
+pub struct LogEntry {
+    pub id: u64,
+    pub timestamp: String,
+    pub kind: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status: Option<u16>,
+    pub detail: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl LogEntry {
+    // Build an entry of the given kind stamped with the current time.
+    pub fn new(kind: &str) -> Self {
+        LogEntry {
+            id: 0,
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            kind: kind.to_string(),
+            method: None,
+            path: None,
+            status: None,
+            detail: None,
+            request_id: None,
+        }
+    }
+
+    // Build a REQ entry for an incoming request.
+    pub fn request(method: &str, path: &str) -> Self {
+        let mut entry = LogEntry::new("REQ");
+        entry.method = Some(method.to_string());
+        entry.path = Some(path.to_string());
+        entry
+    }
+
+    // Build a RES entry for an outgoing response.
+    pub fn response(method: &str, path: &str, status: u16) -> Self {
+        let mut entry = LogEntry::request(method, path);
+        entry.kind = "RES".to_string();
+        entry.status = Some(status);
+        entry
+    }
+
+    // Render as "<timestamp> <KIND> [status] [method] [path] [detail] [req=<id>]".
+    pub fn to_line(&self) -> String {
+        let mut parts = vec![self.timestamp.clone(), self.kind.clone()];
+        if let Some(status) = self.status {
+            parts.push(status.to_string());
+        }
+        parts.extend(self.method.iter().cloned());
+        parts.extend(self.path.iter().cloned());
+        parts.extend(self.detail.iter().cloned());
+        parts.extend(self.request_id.iter().map(|id| format!("req={}", id)));
+        parts.join(" ")
+    }
+
+    // Render as a JSON object with the structured parts and the full line.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "timestamp": self.timestamp,
+            "kind": self.kind,
+            "method": self.method,
+            "path": self.path,
+            "status": self.status,
+            "detail": self.detail,
+            "request_id": self.request_id,
+            "line": self.to_line(),
+        })
+    }
+
+    // Parse a line rendered by to_line back into its parts.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let mut rest = line.trim();
+        let timestamp = next_token(&mut rest)?;
+        let kind = next_token(&mut rest)?;
+        let mut entry = LogEntry {
+            id: 0,
+            timestamp,
+            kind,
+            method: None,
+            path: None,
+            status: None,
+            detail: None,
+            request_id: None,
+        };
+        let mut peek = rest;
+        if let Some(token) = next_token(&mut peek)
+            && token.len() == 3
+            && let Ok(status) = token.parse::<u16>()
+        {
+            entry.status = Some(status);
+            rest = peek;
+        }
+        let mut peek = rest;
+        if let Some(token) = next_token(&mut peek)
+            && token.chars().all(|c| c.is_ascii_uppercase())
+        {
+            entry.method = Some(token);
+            rest = peek;
+        }
+        let mut peek = rest;
+        if let Some(token) = next_token(&mut peek)
+            && token.starts_with('/')
+        {
+            entry.path = Some(token);
+            rest = peek;
+        }
+        let mut detail = rest.trim();
+        if let Some((head, id)) = detail.rsplit_once("req=")
+            && (head.is_empty() || head.ends_with(' '))
+            && is_valid_request_id(id)
+        {
+            entry.request_id = Some(id.to_string());
+            detail = head.trim_end();
+        }
+        if !detail.is_empty() {
+            entry.detail = Some(detail.to_string());
+        }
+        Some(entry)
+    }
+}
+
+// Accept incoming request ids of up to 128 visible ASCII characters.
+pub fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+// Generate a UUID v4 from the OS random source to tag a request.
+pub fn new_request_id() -> String {
+    let mut bytes = [0u8; 16];
+    if getrandom::getrandom(&mut bytes).is_err() {
+        // No OS randomness: fall back to clock and counter, unique only within this process.
+        static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let count = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut rng = FakeRng::new(None);
+        rng.0 ^= count.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        return rng.uuid();
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+// Split the next space-separated token off the front of a string.
+fn next_token(rest: &mut &str) -> Option<String> {
+    let trimmed = rest.trim_start();
+    let end = trimmed.find(' ').unwrap_or(trimmed.len());
+    let token = &trimmed[..end];
+    *rest = &trimmed[end..];
+    (!token.is_empty()).then(|| token.to_string())
 }
 
 struct LogState {
-    sender: broadcast::Sender<String>,
-    buffer: Mutex<VecDeque<String>>,
+    sender: broadcast::Sender<LogEntry>,
+    buffer: Mutex<VecDeque<LogEntry>>,
+    writer: std::sync::mpsc::Sender<String>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+// Fixture change event recorded from the fs watcher.
+#[derive(Clone)]
+pub struct FsChange {
+    pub path: String,
+    pub kind: String,
+    pub timestamp: String,
+}
+
+struct FsState {
+    sender: broadcast::Sender<FsChange>,
+    changes: Mutex<VecDeque<FsChange>>,
 }
 
 static LOG_STATE: OnceLock<LogState> = OnceLock::new();
+static RATE_WINDOWS: OnceLock<Mutex<HashMap<IpAddr, (Instant, u32)>>> = OnceLock::new();
+static FS_STATE: OnceLock<FsState> = OnceLock::new();
+static ASSERTIONS: OnceLock<Mutex<HashMap<(String, String), AssertionStats>>> = OnceLock::new();
+
+// Pass/fail counters for one mapping's request expectation.
+#[derive(Default)]
+struct AssertionStats {
+    passed: u64,
+    failed: u64,
+    last_failure: Vec<String>,
+}
+
+// Addresses the HTTP server binds to, from a comma-separated STUB_BIND (default 127.0.0.1:3000).
+pub fn bind_addresses() -> Vec<String> {
+    let addrs: Vec<String> = std::env::var("STUB_BIND")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|addr| !addr.is_empty())
+        .map(str::to_string)
+        .collect();
+    if addrs.is_empty() {
+        vec!["127.0.0.1:3000".to_string()]
+    } else {
+        addrs
+    }
+}
+
+// Base URL for generated collections: the listeners' scheme and a loopback-reachable address.
+pub fn base_url() -> String {
+    if cfg!(unix) && unix_socket_path().is_some() {
+        return "http://localhost".to_string();
+    }
+    format!("{}://{}", listen_scheme(), client_address(&bind_addresses()))
+}
+
+// Pick the bind address a local client should use, preferring loopback and mapping 0.0.0.0/[::].
+pub fn client_address(addrs: &[String]) -> String {
+    let reachable: Vec<String> = addrs
+        .iter()
+        .map(|addr| match addr.parse::<std::net::SocketAddr>() {
+            Ok(socket) if socket.ip().is_unspecified() => format!("127.0.0.1:{}", socket.port()),
+            _ => addr.clone(),
+        })
+        .collect();
+    reachable
+        .iter()
+        .find(|addr| {
+            addr.parse::<std::net::SocketAddr>()
+                .is_ok_and(|socket| socket.ip().is_loopback())
+        })
+        .or(reachable.first())
+        .cloned()
+        .unwrap_or_else(|| "127.0.0.1:3000".to_string())
+}
+
+// Scheme the TCP listeners speak: https when a TLS certificate is configured.
+fn listen_scheme() -> &'static str {
+    if tls_paths().is_some() { "https" } else { "http" }
+}
+
+// PEM certificate and key paths from STUB_TLS_CERT / STUB_TLS_KEY, when both are set.
+pub fn tls_paths() -> Option<(PathBuf, PathBuf)> {
+    let cert = std::env::var("STUB_TLS_CERT").ok().filter(|v| !v.trim().is_empty())?;
+    let key = std::env::var("STUB_TLS_KEY").ok().filter(|v| !v.trim().is_empty())?;
+    Some((PathBuf::from(cert), PathBuf::from(key)))
+}
+
+// Unix domain socket path from STUB_UNIX_SOCKET, when set.
+pub fn unix_socket_path() -> Option<PathBuf> {
+    let path = std::env::var("STUB_UNIX_SOCKET").ok().filter(|v| !v.trim().is_empty())?;
+    Some(PathBuf::from(path))
+}
+
+// Describe where the server listens, e.g. http://127.0.0.1:3000 or unix:/tmp/stub.sock.
+pub fn listen_description() -> Vec<String> {
+    if cfg!(unix)
+        && let Some(path) = unix_socket_path()
+    {
+        return vec![format!("unix:{}", path.display())];
+    }
+    bind_addresses()
+        .iter()
+        .map(|addr| format!("{}://{}", listen_scheme(), addr))
+        .collect()
+}
+
+// Directory chosen for json/ and config/ instead of the crate directory.
+static ROOT_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+// Data directory from STUB_ROOT, when set.
+pub fn root_dir_from_env() -> Option<PathBuf> {
+    let path = std::env::var("STUB_ROOT").ok().filter(|v| !v.trim().is_empty())?;
+    Some(PathBuf::from(path))
+}
+
+// Serve json/ and config/ from another directory.
+pub fn set_root_dir(dir: PathBuf) {
+    *ROOT_DIR.write().unwrap() = Some(dir);
+}
+
+// Directory holding json/ and config/.
+fn base_root_dir() -> PathBuf {
+    ROOT_DIR
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+}
 
 // Resolve the json/ directory path.
 pub fn base_json_dir() -> PathBuf {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("json")
+    base_root_dir().join("json")
 }
 
 // Resolve the config/ directory path.
 pub fn base_config_dir() -> PathBuf {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config")
+    base_root_dir().join("config")
+}
+
+// Cached contents of config/ files by path; None marks a missing file.
+static CONFIG_CACHE: OnceLock<RwLock<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+
+// Bumped by every invalidation, so a load that raced one is not cached.
+static CONFIG_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+// Set once config/ is watched, so cached entries get invalidated on change.
+static CONFIG_WATCHED: AtomicBool = AtomicBool::new(false);
+
+// Read a config/ file, served from the cache while the config watcher is running.
+pub fn read_config_file(name: &str) -> String {
+    read_config_with(name, load_config_file)
+}
+
+// Cache lookup behind read_config_file, loading misses with the given loader.
+fn read_config_with(name: &str, load: impl FnOnce(&str) -> Option<String>) -> String {
+    if !CONFIG_WATCHED.load(Ordering::Acquire) {
+        return load(name).unwrap_or_default();
+    }
+    let cache = CONFIG_CACHE.get_or_init(Default::default);
+    let key = base_config_dir().join(name);
+    if let Some(cached) = cache.read().unwrap().get(&key) {
+        return cached.clone().unwrap_or_default();
+    }
+    let generation = CONFIG_GENERATION.load(Ordering::Acquire);
+    let contents = load(name);
+    let mut entries = cache.write().unwrap();
+    if CONFIG_GENERATION.load(Ordering::Acquire) == generation {
+        entries.insert(key, contents.clone());
+    }
+    contents.unwrap_or_default()
+}
+
+// Load a config/ file from disk; .txt files lose blank lines and `#` comment lines.
+fn load_config_file(name: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(base_config_dir().join(name)).ok()?;
+    if !name.ends_with(".txt") {
+        return Some(contents);
+    }
+    let kept: Vec<&str> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .collect();
+    Some(kept.join("\n"))
+}
+
+// Write a config/ file and drop its cached copy.
+pub fn write_config_file(name: &str, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let config_dir = base_config_dir();
+    std::fs::create_dir_all(&config_dir)?;
+    let result = write_atomic(&config_dir.join(name), contents.as_ref());
+    invalidate_config_cache();
+    bump_fs_generation();
+    result
+}
+
+// Suffix marking the temporary files used by atomic writes.
+const ATOMIC_TEMP_MARKER: &str = ".tmp-";
+
+// Temporary sibling path an atomic write goes through before the rename.
+fn atomic_temp_path(path: &Path) -> PathBuf {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(
+        ".{}{}{}-{}",
+        name,
+        ATOMIC_TEMP_MARKER,
+        std::process::id(),
+        unique
+    ))
+}
+
+// Whether a path is an in-flight atomic write temp file.
+fn is_atomic_temp(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with('.') && n.contains(ATOMIC_TEMP_MARKER))
+}
+
+// Write a file through a temp sibling and rename, so readers never see a partial file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+// Atomic write with the temp file filled by the given writer; any failure keeps the old file.
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut std::fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let temp = atomic_temp_path(path);
+    let result = std::fs::File::create(&temp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    let result = result.and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+// Async counterpart of write_atomic for request handlers; marks cached renders stale.
+pub async fn write_atomic_async(path: PathBuf, contents: Vec<u8>) -> std::io::Result<()> {
+    let result = tokio::task::spawn_blocking(move || write_atomic(&path, &contents))
+        .await
+        .unwrap_or_else(|err| Err(std::io::Error::other(err)));
+    bump_fs_generation();
+    result
+}
+
+// Forget every cached config file so the next read hits the disk.
+pub fn invalidate_config_cache() {
+    let cache = CONFIG_CACHE.get_or_init(Default::default);
+    let mut entries = cache.write().unwrap();
+    CONFIG_GENERATION.fetch_add(1, Ordering::AcqRel);
+    entries.clear();
+    if let Some(regexes) = REGEX_CACHE.get() {
+        regexes.lock().unwrap().clear();
+    }
 }
 
 // Load refresh endpoint from config or default.
 pub fn read_refresh_endpoint() -> String {
-    let path = base_config_dir().join("refresh_endpoint.txt");
-    let contents = std::fs::read_to_string(path).unwrap_or_default();
+    let contents = read_config_file("refresh_endpoint.txt");
     let trimmed = contents.trim();
     if trimmed.is_empty() {
         "/api/v1/authentication/refresh".to_string()
@@ -43,10 +502,31 @@ pub fn read_refresh_endpoint() -> String {
     }
 }
 
+// Load the echo endpoint path; empty (default) disables it.
+pub fn read_echo_endpoint() -> Option<String> {
+    let contents = read_config_file("echo_endpoint.txt");
+    let trimmed = contents.trim();
+    trimmed.starts_with("/api/").then(|| trimmed.to_string())
+}
+
+// Load the header names masked in echo responses (default authorization; off shows all).
+pub fn read_echo_redact() -> Vec<String> {
+    let contents = read_config_file("echo_redact.txt");
+    if contents.trim().is_empty() {
+        return vec!["authorization".to_string()];
+    }
+    if contents.trim().eq_ignore_ascii_case("off") {
+        return Vec::new();
+    }
+    contents
+        .split_whitespace()
+        .map(|name| name.to_ascii_lowercase())
+        .collect()
+}
+
 // Load ping endpoint from config or default.
 pub fn read_ping_endpoint() -> String {
-    let path = base_config_dir().join("ping_endpoint.txt");
-    let contents = std::fs::read_to_string(path).unwrap_or_default();
+    let contents = read_config_file("ping_endpoint.txt");
     let trimmed = contents.trim();
     if trimmed.is_empty() {
         "/api/v1/ping".to_string()
@@ -55,32 +535,917 @@ pub fn read_ping_endpoint() -> String {
     }
 }
 
-// Load log ignore patterns with defaults for / and /events.
-pub fn read_log_ignore_patterns() -> Vec<String> {
-    let mut defaults = vec!["/".to_string(), "/events".to_string()];
-    let path = base_config_dir().join("log_ignore.txt");
-    let contents = std::fs::read_to_string(path).unwrap_or_default();
-    let mut from_file: Vec<String> = contents
-        .lines()
-        .filter_map(|line| normalize_log_pattern(line))
-        .collect();
-    defaults.append(&mut from_file);
-    defaults
+// Load log ignore patterns with defaults for /, /events, /events/fs and /version.
+pub fn read_log_ignore_patterns() -> Vec<String> {
+    let mut defaults = vec![
+        "/".to_string(),
+        "/events".to_string(),
+        "/events/fs".to_string(),
+        "/version".to_string(),
+    ];
+    let contents = read_config_file("log_ignore.txt");
+    let mut from_file: Vec<String> = contents
+        .lines()
+        .filter_map(normalize_log_pattern)
+        .collect();
+    defaults.append(&mut from_file);
+    defaults
+}
+
+// Load the delay added to every response, as a millisecond range (default none).
+pub fn read_global_delay() -> Option<(u64, u64)> {
+    parse_delay_range(read_config_file("global_delay.txt").trim()).filter(|range| range.1 > 0)
+}
+
+// Load path patterns exempt from the global delay, same syntax as log_ignore.txt.
+pub fn read_global_delay_exempt() -> Vec<String> {
+    read_config_file("global_delay_exempt.txt")
+        .lines()
+        .filter_map(normalize_log_pattern)
+        .collect()
+}
+
+// Load a ping/refresh fallback body from config, or the built-in one when absent or invalid.
+pub fn read_fallback_json(name: &str, builtin: &str) -> Vec<u8> {
+    let contents = read_config_file(name);
+    if contents.trim().is_empty() {
+        return builtin.as_bytes().to_vec();
+    }
+    match serde_json::from_str::<serde_json::Value>(&contents) {
+        Ok(_) => contents.into_bytes(),
+        Err(err) => {
+            tracing::warn!(file = name, error = %err, "invalid fallback JSON, using built-in");
+            builtin.as_bytes().to_vec()
+        }
+    }
+}
+
+// Token lifetime in seconds used by the refresh placeholders when none is configured.
+const DEFAULT_REFRESH_EXPIRY_SECS: i64 = 60;
+
+// Load the refresh token lifetime in seconds from config/refresh_expiry.txt (default 60).
+pub fn read_refresh_expiry() -> i64 {
+    let contents = read_config_file("refresh_expiry.txt");
+    match contents.trim().parse::<i64>() {
+        Ok(secs) if secs >= 0 => secs,
+        _ => DEFAULT_REFRESH_EXPIRY_SECS,
+    }
+}
+
+// Fill {{now}}, {{expiry}}, {{now+expiry}} and {{nonce}} in a refresh body; other bodies pass through.
+pub fn fill_refresh_placeholders(bytes: Vec<u8>, expiry: i64) -> Vec<u8> {
+    static NONCE: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    const PLACEHOLDERS: [&str; 4] = ["{{now}}", "{{expiry}}", "{{now+expiry}}", "{{nonce}}"];
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) => return err.into_bytes(),
+    };
+    if !PLACEHOLDERS.iter().any(|p| text.contains(p)) {
+        return text.into_bytes();
+    }
+    let now = chrono::Utc::now().timestamp();
+    let nonce = NONCE.fetch_add(1, Ordering::Relaxed) + 1;
+    text.replace("{{now+expiry}}", &(now + expiry).to_string())
+        .replace("{{now}}", &now.to_string())
+        .replace("{{expiry}}", &expiry.to_string())
+        .replace("{{nonce}}", &nonce.to_string())
+        .into_bytes()
+}
+
+// Seconds a rendered dashboard is reused when no config value is set.
+const DEFAULT_INDEX_CACHE_TTL_SECS: u64 = 5;
+
+// Load how long the rendered dashboard is reused from config/index_cache_ttl.txt (0 disables).
+pub fn read_index_cache_ttl() -> Duration {
+    let contents = read_config_file("index_cache_ttl.txt");
+    let secs = contents.trim().parse().unwrap_or(DEFAULT_INDEX_CACHE_TTL_SECS);
+    Duration::from_secs(secs)
+}
+
+// A rendered dashboard and the watch generation it was built from.
+struct CachedPage {
+    lang: &'static str,
+    generation: u64,
+    rendered_at: Instant,
+    html: String,
+}
+
+// Rendered dashboards, one per UI language.
+static DASHBOARD_CACHE: Mutex<Vec<CachedPage>> = Mutex::new(Vec::new());
+
+// Return the cached dashboard for a language while it is younger than ttl and no file changed.
+pub fn cached_dashboard(lang: &str, ttl: Duration) -> Option<String> {
+    if ttl.is_zero() {
+        return None;
+    }
+    let generation = fs_generation();
+    DASHBOARD_CACHE
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|page| {
+            page.lang == lang && page.generation == generation && page.rendered_at.elapsed() < ttl
+        })
+        .map(|page| page.html.clone())
+}
+
+// Keep a freshly rendered dashboard, built from the given watch generation.
+pub fn store_dashboard(lang: &'static str, generation: u64, html: String) {
+    let mut cache = DASHBOARD_CACHE.lock().unwrap();
+    cache.retain(|page| page.lang != lang);
+    cache.push(CachedPage {
+        lang,
+        generation,
+        rendered_at: Instant::now(),
+        html,
+    });
+}
+
+// Load the global log enabled toggle (default on).
+pub fn read_log_enabled() -> bool {
+    let contents = read_config_file("log_enabled.txt");
+    contents.trim().is_empty() || is_enabled_value(&contents)
+}
+
+// Upload caps for a folder: bytes per uploaded file and files the folder may hold.
+pub struct UploadLimits {
+    pub max_bytes: usize,
+    pub max_files: Option<usize>,
+}
+
+// Upload size allowed per file when no .limits file applies.
+const DEFAULT_UPLOAD_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+// Read the nearest .limits file from the folder up to its top-level subdir.
+pub fn read_upload_limits(folder: &str) -> UploadLimits {
+    let mut limits = UploadLimits {
+        max_bytes: DEFAULT_UPLOAD_MAX_BYTES,
+        max_files: None,
+    };
+    let mut current = folder;
+    let contents = loop {
+        if let Ok(contents) = std::fs::read_to_string(json_path(current).join(".limits")) {
+            break contents;
+        }
+        match current.rsplit_once('/') {
+            Some((parent, _)) => current = parent,
+            None => return limits,
+        }
+    };
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<usize>() else {
+            continue;
+        };
+        match key.trim() {
+            "max_bytes" => limits.max_bytes = value,
+            "max_files" => limits.max_files = Some(value),
+            _ => {}
+        }
+    }
+    limits
+}
+
+// Load the upload JSON validation toggle (default on).
+pub fn read_validate_uploads() -> bool {
+    let contents = read_config_file("validate_uploads.txt");
+    contents.trim().is_empty() || is_enabled_value(&contents)
+}
+
+// Load the served JSON output format: raw (default), pretty or minified.
+pub fn read_json_output() -> String {
+    let contents = read_config_file("json_output.txt");
+    match contents.trim().to_lowercase().as_str() {
+        "pretty" => "pretty".to_string(),
+        "minified" => "minified".to_string(),
+        _ => "raw".to_string(),
+    }
+}
+
+// Re-serialize JSON bytes per the output format, keeping invalid JSON verbatim.
+pub fn format_json_output(bytes: Vec<u8>, format: &str) -> Vec<u8> {
+    if format == "raw" {
+        return bytes;
+    }
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return bytes;
+    };
+    let formatted = if format == "pretty" {
+        serde_json::to_vec_pretty(&value)
+    } else {
+        serde_json::to_vec(&value)
+    };
+    formatted.unwrap_or(bytes)
+}
+
+// Load the envelope template for a fixture: per-subdir first, then global.
+pub fn read_envelope_template(file: &str) -> Option<String> {
+    let config_dir = base_config_dir();
+    let subdir = file.split('/').next().unwrap_or_default();
+    let mut candidates = Vec::new();
+    if is_safe_segment(subdir) {
+        candidates.push(config_dir.join("envelopes").join(format!("{}.json", subdir)));
+    }
+    candidates.push(config_dir.join("envelope.json"));
+    candidates
+        .into_iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .find(|contents| contents.contains("{{payload}}"))
+}
+
+// Load the key that marks a fixture as already wrapped, if configured.
+pub fn read_envelope_skip_key() -> Option<String> {
+    let contents = read_config_file("envelope_skip.txt");
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+// Wrap a valid JSON fixture into the configured envelope template.
+pub fn wrap_envelope(bytes: Vec<u8>, file: &str) -> Vec<u8> {
+    let Some(template) = read_envelope_template(file) else {
+        return bytes;
+    };
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return bytes;
+    };
+    if let Some(key) = read_envelope_skip_key()
+        && payload.get(&key).is_some()
+    {
+        return bytes;
+    }
+    let wrapped = template.replace("{{payload}}", &payload.to_string());
+    if serde_json::from_str::<serde_json::Value>(&wrapped).is_err() {
+        return bytes;
+    }
+    wrapped.into_bytes()
+}
+
+// Load the upstream base URL for unmapped /api routes, without trailing slash.
+pub fn read_upstream() -> Option<String> {
+    let contents = read_config_file("upstream.txt");
+    let trimmed = contents.trim().trim_end_matches('/');
+    if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
+        Some(trimmed.to_string())
+    } else {
+        None
+    }
+}
+
+// Name the built-in endpoint (ping or refresh) that takes priority over a mapping, if any.
+pub fn shadowing_builtin(method: &str, path: &str) -> Option<&'static str> {
+    if method == "GET" && read_ping_endpoint() == path {
+        Some("ping")
+    } else if method == "POST" && read_refresh_endpoint() == path {
+        Some("refresh")
+    } else {
+        None
+    }
+}
+
+// Load the fallback fixture (relative to json/) served for unmatched /api routes.
+pub fn read_api_fallback() -> Option<String> {
+    let contents = read_config_file("api_fallback.txt");
+    let trimmed = contents.trim().trim_start_matches("json/");
+    if trimmed.is_empty() || !is_safe_rel_path(trimmed) {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+// Load the status code used with the fallback fixture (default 404).
+pub fn read_api_fallback_status() -> u16 {
+    let contents = read_config_file("api_fallback_status.txt");
+    contents
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|code| (100..=599).contains(code))
+        .unwrap_or(404)
+}
+
+// Load the read-only toggle from config/readonly.txt or STUB_READONLY (default off).
+pub fn read_readonly() -> bool {
+    if std::env::var("STUB_READONLY").is_ok_and(|v| is_enabled_value(&v)) {
+        return true;
+    }
+    let contents = read_config_file("readonly.txt");
+    is_enabled_value(&contents)
+}
+
+// Whether structured JSON access logs are requested via STUB_LOG_JSON.
+pub fn log_json_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| std::env::var("STUB_LOG_JSON").is_ok_and(|v| is_enabled_value(&v)))
+}
+
+// Load the toggle refusing uploads without a .json (or .csv/.yaml/.yml) extension (default off).
+pub fn read_require_json_ext() -> bool {
+    let contents = read_config_file("require_json_ext.txt");
+    is_enabled_value(&contents)
+}
+
+// Whether a file name has an extension served as JSON: .json, or .csv/.yaml/.yml converted on read.
+pub fn has_fixture_extension(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    [".json", ".csv", ".yaml", ".yml"].iter().any(|ext| lower.ends_with(ext))
+}
+
+// Load the toggle stripping a BOM and trailing whitespace from served JSON (default off).
+pub fn read_normalize_output() -> bool {
+    let contents = read_config_file("normalize_output.txt");
+    is_enabled_value(&contents)
+}
+
+// Drop a leading UTF-8 BOM and trailing whitespace from a JSON body.
+pub fn strip_bom_and_trailing(mut bytes: Vec<u8>) -> Vec<u8> {
+    if bytes.starts_with(b"\xEF\xBB\xBF") {
+        bytes.drain(..3);
+    }
+    let end = bytes.iter().rposition(|b| !b.is_ascii_whitespace()).map_or(0, |i| i + 1);
+    bytes.truncate(end);
+    bytes
+}
+
+// Load ${VAR} substitution for served JSON: None when off (default), Some(strict) otherwise.
+pub fn read_env_substitution() -> Option<bool> {
+    let contents = read_config_file("env_substitution.txt");
+    match contents.trim() {
+        "strict" => Some(true),
+        value if is_enabled_value(value) => Some(false),
+        _ => None,
+    }
+}
+
+// Replace ${VAR} and ${VAR:-default} with process env values; $${ emits a literal ${.
+pub fn substitute_env(bytes: Vec<u8>, strict: bool) -> Result<Vec<u8>, String> {
+    let Ok(text) = std::str::from_utf8(&bytes) else {
+        return Ok(bytes);
+    };
+    if !text.contains("${") {
+        return Ok(bytes);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let (name, default) = match after[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&after[..end], None),
+        };
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        match (valid, std::env::var(name).ok(), default) {
+            (false, _, _) => out.push_str(&rest[start..start + 3 + end]),
+            (true, Some(value), _) => out.push_str(&value),
+            (true, None, Some(default)) => out.push_str(default),
+            (true, None, None) if strict => {
+                return Err(format!("environment variable {} is not set", name));
+            }
+            (true, None, None) => out.push_str(&rest[start..start + 3 + end]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out.into_bytes())
+}
+
+// Load the record mode toggle (default off).
+pub fn read_record_enabled() -> bool {
+    let contents = read_config_file("record.txt");
+    is_enabled_value(&contents)
+}
+
+// Derive a safe fixture path (without extension) under prefix/ from an API path.
+pub fn derive_fixture_path(prefix: &str, api_path: &str) -> Option<String> {
+    let rest = api_path.strip_prefix("/api/").unwrap_or(api_path);
+    let segments: Vec<String> = rest
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let cleaned: String = segment
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+                .collect();
+            if cleaned.chars().all(|c| c == '.') {
+                "_".to_string()
+            } else {
+                cleaned
+            }
+        })
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    let derived = format!("{}/{}", prefix, segments.join("/"));
+    is_safe_rel_path(&derived).then_some(derived)
+}
+
+// Operation extracted from an OpenAPI document: method, /api path and example body.
+pub struct OpenApiOperation {
+    pub method: String,
+    pub path: String,
+    pub example: serde_json::Value,
+}
+
+// Parse a YAML or JSON OpenAPI spec into operations; Err lists the skipped ones.
+pub fn parse_openapi_operations(bytes: &[u8]) -> Result<(Vec<OpenApiOperation>, Vec<String>), String> {
+    let spec: serde_json::Value = serde_yaml::from_slice(bytes).map_err(|err| err.to_string())?;
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) else {
+        return Err("missing paths object".to_string());
+    };
+    let mut operations = Vec::new();
+    let mut skipped = Vec::new();
+    for (raw_path, item) in paths {
+        let Some(item) = item.as_object() else {
+            continue;
+        };
+        for (method, operation) in item {
+            let method = method.to_uppercase();
+            if !["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE"].contains(&method.as_str()) {
+                continue;
+            }
+            let path = if raw_path.starts_with("/api/") {
+                raw_path.clone()
+            } else {
+                format!("/api{}", raw_path)
+            };
+            if (method != "GET" && method != "POST")
+                || !is_safe_rel_path(path.trim_start_matches('/'))
+            {
+                skipped.push(format!("{} {}", method, raw_path));
+                continue;
+            }
+            let example = openapi_example(operation).unwrap_or_else(|| serde_json::json!({}));
+            operations.push(OpenApiOperation { method, path, example });
+        }
+    }
+    Ok((operations, skipped))
+}
+
+// Find the example JSON body of an operation's first success response.
+fn openapi_example(operation: &serde_json::Value) -> Option<serde_json::Value> {
+    let responses = operation.get("responses")?.as_object()?;
+    let response = responses
+        .iter()
+        .filter(|(code, _)| code.starts_with('2'))
+        .chain(responses.iter().filter(|(code, _)| *code == "default"))
+        .map(|(_, response)| response)
+        .next()?;
+    let content = response.get("content")?.as_object()?;
+    let media = content
+        .iter()
+        .find(|(kind, _)| kind.contains("json"))
+        .map(|(_, media)| media)?;
+    if let Some(example) = media.get("example") {
+        return Some(example.clone());
+    }
+    if let Some(example) = media
+        .get("examples")
+        .and_then(|e| e.as_object())
+        .and_then(|e| e.values().next())
+        .and_then(|e| e.get("value"))
+    {
+        return Some(example.clone());
+    }
+    media.get("schema")?.get("example").cloned()
+}
+
+// Infer a minimal JSON Schema describing a JSON value.
+pub fn infer_json_schema(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::{json, Value};
+    match value {
+        Value::Null => json!({ "nullable": true }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "type": "integer" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => {
+            let item_schema = items.first().map(infer_json_schema).unwrap_or_else(|| json!({}));
+            json!({ "type": "array", "items": item_schema })
+        }
+        Value::Object(map) => {
+            let properties: serde_json::Map<String, Value> = map
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_json_schema(value)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+// Small deterministic PRNG (splitmix64) for schema-driven fake data.
+pub struct FakeRng(u64);
+
+impl FakeRng {
+    // Seed from a number, or from the clock when none is given.
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        FakeRng(seed)
+    }
+
+    // Return the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Pick an integer in the inclusive range [min, max].
+    pub fn range(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        let span = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    // Format the next 128 random bits as a UUID v4.
+    pub fn uuid(&mut self) -> String {
+        let (a, b) = (self.next_u64(), self.next_u64());
+        format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            a >> 32,
+            (a >> 16) & 0xffff,
+            a & 0xfff,
+            (b >> 48) & 0x3fff | 0x8000,
+            b & 0xffff_ffff_ffff
+        )
+    }
+
+    // Pick a float in [min, max).
+    pub fn float(&mut self, min: f64, max: f64) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        min + unit * (max - min)
+    }
+}
+
+// Parse a seed query value: numbers are used as-is, other text is hashed.
+pub fn parse_seed(value: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    value.parse::<u64>().unwrap_or_else(|_| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+// Generate random data matching a JSON Schema, or None if it doesn't look like one.
+pub fn generate_from_schema(schema: &serde_json::Value, rng: &mut FakeRng) -> Option<serde_json::Value> {
+    const SCHEMA_KEYS: &[&str] = &["type", "properties", "items", "enum", "const", "oneOf", "anyOf", "allOf", "$ref"];
+    let object = schema.as_object()?;
+    if !SCHEMA_KEYS.iter().any(|key| object.contains_key(*key)) {
+        return None;
+    }
+    Some(fake_value(schema, schema, rng, 0))
+}
+
+// Produce one value for a (sub)schema, resolving local $refs against the root.
+fn fake_value(
+    schema: &serde_json::Value,
+    root: &serde_json::Value,
+    rng: &mut FakeRng,
+    depth: usize,
+) -> serde_json::Value {
+    use serde_json::{json, Value};
+    const WORDS: &[&str] = &["alpha", "bravo", "delta", "echo", "lima", "nova", "orbit", "pixel", "quartz", "sierra"];
+    if depth > 8 {
+        return Value::Null;
+    }
+    let Some(object) = schema.as_object() else {
+        return Value::Null;
+    };
+    if let Some(value) = object.get("const") {
+        return value.clone();
+    }
+    if let Some(Value::Array(choices)) = object.get("enum")
+        && !choices.is_empty()
+    {
+        return choices[rng.range(0, choices.len() as i64 - 1) as usize].clone();
+    }
+    if let Some(target) = object.get("$ref").and_then(Value::as_str) {
+        return match target.strip_prefix('#').and_then(|pointer| root.pointer(pointer)) {
+            Some(resolved) => fake_value(resolved, root, rng, depth + 1),
+            None => Value::Null,
+        };
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(options)) = object.get(key)
+            && !options.is_empty()
+        {
+            let pick = &options[rng.range(0, options.len() as i64 - 1) as usize];
+            return fake_value(pick, root, rng, depth + 1);
+        }
+    }
+    if let Some(Value::Array(parts)) = object.get("allOf") {
+        let mut merged = serde_json::Map::new();
+        for part in parts {
+            match fake_value(part, root, rng, depth + 1) {
+                Value::Object(map) => merged.extend(map),
+                other if merged.is_empty() => return other,
+                _ => {}
+            }
+        }
+        return Value::Object(merged);
+    }
+
+    let kind = match object.get("type") {
+        Some(Value::String(kind)) => kind.as_str(),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null")
+            .unwrap_or("null"),
+        _ if object.contains_key("properties") => "object",
+        _ if object.contains_key("items") => "array",
+        _ => "string",
+    };
+    let int_of = |key: &str| object.get(key).and_then(Value::as_i64);
+    let float_of = |key: &str| object.get(key).and_then(Value::as_f64);
+    match kind {
+        "null" => Value::Null,
+        "boolean" => Value::Bool(rng.next_u64() & 1 == 1),
+        "integer" => {
+            let min = int_of("minimum").or(int_of("exclusiveMinimum").map(|v| v + 1)).unwrap_or(0);
+            let max = int_of("maximum")
+                .or(int_of("exclusiveMaximum").map(|v| v - 1))
+                .unwrap_or(min.saturating_add(1000));
+            json!(rng.range(min, max))
+        }
+        "number" => {
+            let min = float_of("minimum").or(float_of("exclusiveMinimum")).unwrap_or(0.0);
+            let max = float_of("maximum").or(float_of("exclusiveMaximum")).unwrap_or(min + 1000.0);
+            json!((rng.float(min, max) * 100.0).round() / 100.0)
+        }
+        "array" => {
+            let min = int_of("minItems").unwrap_or(1).max(0);
+            let max = int_of("maxItems").unwrap_or(min.max(1) + 4).max(min);
+            let items = object.get("items").cloned().unwrap_or_else(|| json!({}));
+            let count = rng.range(min, max);
+            Value::Array((0..count).map(|_| fake_value(&items, root, rng, depth + 1)).collect())
+        }
+        "object" => {
+            let mut map = serde_json::Map::new();
+            if let Some(Value::Object(properties)) = object.get("properties") {
+                for (key, property) in properties {
+                    map.insert(key.clone(), fake_value(property, root, rng, depth + 1));
+                }
+            }
+            Value::Object(map)
+        }
+        _ => {
+            let value = match object.get("format").and_then(Value::as_str) {
+                Some("date-time") => chrono::DateTime::from_timestamp(rng.range(1_500_000_000, 1_900_000_000), 0)
+                    .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                    .unwrap_or_default(),
+                Some("date") => chrono::DateTime::from_timestamp(rng.range(1_500_000_000, 1_900_000_000), 0)
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+                Some("email") => format!("{}{}@example.com", WORDS[rng.range(0, 9) as usize], rng.range(1, 99)),
+                Some("uri") | Some("url") => format!("https://example.com/{}", WORDS[rng.range(0, 9) as usize]),
+                Some("uuid") => rng.uuid(),
+                _ => {
+                    let min = int_of("minLength").unwrap_or(0).max(0) as usize;
+                    let max = int_of("maxLength").map(|v| v.max(0) as usize);
+                    let mut text = WORDS[rng.range(0, 9) as usize].to_string();
+                    while text.len() < min {
+                        text.push('-');
+                        text.push_str(WORDS[rng.range(0, 9) as usize]);
+                    }
+                    if let Some(max) = max {
+                        text.truncate(max.max(min));
+                    }
+                    text
+                }
+            };
+            Value::String(value)
+        }
+    }
+}
+
+// Check a JSONP callback name: dot-separated JS identifiers, at most 128 chars.
+pub fn is_safe_js_callback(name: &str) -> bool {
+    name.len() <= 128
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        })
+}
+
+// Wrap a JSON body as a JSONP call; the leading comment guards against content sniffing.
+pub fn wrap_jsonp(bytes: Vec<u8>, callback: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() + callback.len() + 8);
+    out.extend_from_slice(b"/**/");
+    out.extend_from_slice(callback.as_bytes());
+    out.push(b'(');
+    out.extend_from_slice(&bytes);
+    out.extend_from_slice(b");");
+    out
+}
+
+// Compute a strong ETag from the served bytes and the file's mtime.
+pub fn compute_etag(bytes: &[u8], modified: Option<std::time::SystemTime>) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    if let Some(modified) = modified
+        && let Ok(since) = modified.duration_since(std::time::UNIX_EPOCH)
+    {
+        since.as_nanos().hash(&mut hasher);
+    }
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// Check whether an If-None-Match header value matches the given ETag.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    })
+}
+
+// Headers that only describe one connection and must not be forwarded by a proxy.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+// Whether a lowercase header name is hop-by-hop.
+pub fn is_hop_by_hop_header(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS.contains(&name)
+}
+
+// Load the bearer token required on /api/* (None disables auth).
+pub fn read_api_token() -> Option<String> {
+    let contents = read_config_file("api_token.txt");
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+// Load which built-in endpoints (ping, refresh) bypass the auth gate.
+pub fn read_auth_bypass() -> Vec<String> {
+    let contents = read_config_file("auth_bypass.txt");
+    contents
+        .split_whitespace()
+        .map(|v| v.to_lowercase())
+        .filter(|v| v == "ping" || v == "refresh")
+        .collect()
+}
+
+// Load the rate limit as (max requests, window), e.g. 60/60s; None disables it.
+pub fn read_rate_limit() -> Option<(u32, Duration)> {
+    let contents = read_config_file("rate_limit.txt");
+    parse_rate_limit(contents.trim())
+}
+
+// Parse N/<window> where window is seconds with an optional s, m or h suffix.
+pub fn parse_rate_limit(spec: &str) -> Option<(u32, Duration)> {
+    let (count, window) = spec.split_once('/')?;
+    let count = count.trim().parse::<u32>().ok().filter(|c| *c > 0)?;
+    let window = window.trim();
+    let (digits, unit) = match window.chars().last()? {
+        's' => (&window[..window.len() - 1], 1),
+        'm' => (&window[..window.len() - 1], 60),
+        'h' => (&window[..window.len() - 1], 3600),
+        _ => (window, 1),
+    };
+    let secs = digits.parse::<u64>().ok().filter(|s| *s > 0)? * unit;
+    Some((count, Duration::from_secs(secs)))
+}
+
+// Count a request for ip in its fixed window; Err carries the time until reset.
+pub fn check_rate_limit(ip: IpAddr, max: u32, window: Duration) -> Result<u32, Duration> {
+    let windows = RATE_WINDOWS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut windows = windows.lock().unwrap();
+    let now = Instant::now();
+    windows.retain(|_, (start, _)| now.duration_since(*start) < window);
+    let (start, count) = windows.entry(ip).or_insert((now, 0));
+    if *count >= max {
+        return Err(window.saturating_sub(now.duration_since(*start)));
+    }
+    *count += 1;
+    Ok(max - *count)
+}
+
+// Load the global request body limit in bytes for mapped routes.
+pub fn read_max_body_bytes() -> usize {
+    let contents = read_config_file("max_body_bytes.txt");
+    contents.trim().parse::<usize>().unwrap_or(2 * 1024 * 1024)
+}
+
+// Load the cap on any request body in bytes, applied before the handlers (default 16 MiB).
+pub fn read_max_request_bytes() -> usize {
+    let contents = read_config_file("max_request_bytes.txt");
+    contents.trim().parse::<usize>().unwrap_or(16 * 1024 * 1024)
+}
+
+// Load the cap on a whole multipart upload request in bytes (default 256 MiB).
+pub fn read_max_upload_request_bytes() -> usize {
+    let contents = read_config_file("max_upload_request_bytes.txt");
+    contents.trim().parse::<usize>().unwrap_or(256 * 1024 * 1024)
+}
+
+// Load how many log lines a slow /events subscriber may fall behind before lines drop.
+pub fn read_log_channel_capacity() -> usize {
+    let contents = read_config_file("log_channel_capacity.txt");
+    contents
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|v| *v > 0)
+        .unwrap_or(256)
+}
+
+// Load the dashboard SSE keep-alive interval in seconds (default 15, 0 disables it).
+pub fn read_sse_keepalive() -> Option<Duration> {
+    let contents = read_config_file("sse_keepalive.txt");
+    let secs = contents.trim().parse::<u64>().unwrap_or(15);
+    (secs > 0).then(|| Duration::from_secs(secs))
 }
 
-// Load the global log enabled toggle (default on).
-pub fn read_log_enabled() -> bool {
-    let path = base_config_dir().join("log_enabled.txt");
-    let contents = std::fs::read_to_string(path).unwrap_or_default();
-    let trimmed = contents.trim().to_lowercase();
-    trimmed.is_empty() || trimmed == "on" || trimmed == "true" || trimmed == "1"
+// Load the request log persistence toggle (default off).
+pub fn read_log_persist() -> bool {
+    let contents = read_config_file("log_persist.txt");
+    is_enabled_value(&contents)
+}
+
+// Load the line count at which the persisted request log rotates.
+pub fn read_log_persist_max() -> usize {
+    let contents = read_config_file("log_persist_max.txt");
+    contents
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|v| *v > 0)
+        .unwrap_or(1000)
 }
 
 // Check whether a path matches any ignore pattern.
 pub fn is_log_ignored(path: &str) -> bool {
-    let patterns = read_log_ignore_patterns();
+    matches_path_pattern(read_log_ignore_patterns(), path)
+}
+
+// Compiled `re:` patterns by expression; None marks one that doesn't compile.
+static REGEX_CACHE: OnceLock<Mutex<HashMap<String, Option<regex::Regex>>>> = OnceLock::new();
+
+// Compile a pattern expression once, reusing it until the config cache is invalidated.
+fn cached_regex(expr: &str) -> Option<regex::Regex> {
+    let cache = REGEX_CACHE.get_or_init(Default::default);
+    if let Some(compiled) = cache.lock().unwrap().get(expr) {
+        return compiled.clone();
+    }
+    let compiled = regex::Regex::new(expr).ok();
+    cache.lock().unwrap().insert(expr.to_string(), compiled.clone());
+    compiled
+}
+
+// Check a path against exact, `/*` prefix and `re:` patterns.
+pub fn matches_path_pattern(patterns: Vec<String>, path: &str) -> bool {
     for pattern in patterns {
-        if pattern.ends_with("/*") {
+        if let Some(expr) = pattern.strip_prefix("re:") {
+            if cached_regex(expr).is_some_and(|re| re.is_match(path)) {
+                return true;
+            }
+        } else if pattern.ends_with("/*") {
             let prefix = &pattern[..pattern.len() - 1];
             let base = prefix.trim_end_matches('/');
             if path == base || path == prefix || path.starts_with(prefix) {
@@ -96,9 +1461,12 @@ pub fn is_log_ignored(path: &str) -> bool {
 // Normalize and validate log ignore patterns.
 pub fn normalize_log_pattern(input: &str) -> Option<String> {
     let raw = input.trim();
-    if raw.is_empty() {
+    if raw.is_empty() || raw.starts_with('#') {
         return None;
     }
+    if let Some(expr) = raw.strip_prefix("re:") {
+        return cached_regex(expr).map(|_| raw.to_string());
+    }
     let normalized = if raw.starts_with('/') {
         raw.to_string()
     } else {
@@ -113,22 +1481,72 @@ pub fn normalize_log_pattern(input: &str) -> Option<String> {
     Some(normalized)
 }
 
-// Parse a urlencoded form field value by key.
+// Split a urlencoded body into decoded (key, raw value bytes) pairs.
+fn form_pairs(body: &str) -> impl Iterator<Item = (String, Vec<u8>)> + '_ {
+    body.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        (url_decode(k), url_decode_bytes(v))
+    })
+}
+
+// Parse a urlencoded form field value by key (first occurrence).
 pub fn form_value(body: &str, key: &str) -> Option<String> {
-    body.split('&').find_map(|pair| {
-        let mut parts = pair.splitn(2, '=');
-        let k = parts.next()?;
-        let v = parts.next().unwrap_or_default();
-        if k == key {
-            Some(url_decode(v))
+    form_pairs(body)
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| String::from_utf8_lossy(&v).into_owned())
+}
+
+// Decode a query string into a JSON object; repeated keys become arrays.
+pub fn query_to_json(query: &str) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for (key, value) in form_pairs(query) {
+        let value = serde_json::Value::from(String::from_utf8_lossy(&value).into_owned());
+        match object.get_mut(&key) {
+            Some(serde_json::Value::Array(items)) => items.push(value),
+            Some(existing) => *existing = serde_json::json!([existing.take(), value]),
+            None => {
+                object.insert(key, value);
+            }
+        }
+    }
+    serde_json::Value::Object(object)
+}
+
+// Parse every value of a repeated urlencoded form field, in order.
+pub fn form_values(body: &str, key: &str) -> Vec<String> {
+    form_pairs(body)
+        .filter(|(k, _)| k == key)
+        .map(|(_, v)| String::from_utf8_lossy(&v).into_owned())
+        .collect()
+}
+
+// Parse a urlencoded form field value by key, rejecting values that decode to invalid UTF-8.
+pub fn form_value_utf8(body: &str, key: &str) -> Option<String> {
+    form_pairs(body)
+        .find(|(k, _)| k == key)
+        .and_then(|(_, v)| String::from_utf8(v).ok())
+}
+
+// Percent-encode each segment of a /-separated path for use in a URL.
+pub fn url_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            encoded.push(byte as char);
         } else {
-            None
+            encoded.push_str(&format!("%{:02X}", byte));
         }
-    })
+    }
+    encoded
 }
 
-// Decode application/x-www-form-urlencoded values.
+// Decode application/x-www-form-urlencoded values, replacing invalid UTF-8.
 pub fn url_decode(input: &str) -> String {
+    String::from_utf8_lossy(&url_decode_bytes(input)).into_owned()
+}
+
+// Decode urlencoded bytes; a dangling % or %X is kept literally.
+pub fn url_decode_bytes(input: &str) -> Vec<u8> {
     let bytes = input.as_bytes();
     let mut out = Vec::with_capacity(bytes.len());
     let mut i = 0;
@@ -138,12 +1556,14 @@ pub fn url_decode(input: &str) -> String {
                 out.push(b' ');
                 i += 1;
             }
-            b'%' if i + 2 < bytes.len() => {
-                if let (Some(h), Some(l)) = (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {
+            b'%' => {
+                let hi = bytes.get(i + 1).copied().and_then(from_hex);
+                let lo = bytes.get(i + 2).copied().and_then(from_hex);
+                if let (Some(h), Some(l)) = (hi, lo) {
                     out.push(h << 4 | l);
                     i += 3;
                 } else {
-                    out.push(bytes[i]);
+                    out.push(b'%');
                     i += 1;
                 }
             }
@@ -153,7 +1573,7 @@ pub fn url_decode(input: &str) -> String {
             }
         }
     }
-    String::from_utf8_lossy(&out).into_owned()
+    out
 }
 
 // Convert a hex digit to a numeric value.
@@ -175,8 +1595,44 @@ pub fn is_safe_segment(segment: &str) -> bool {
         && !segment.contains('\\')
 }
 
-// Validate a relative path (no traversal or prefixes).
+// Bring a URL or form path to / separators; backslashes only separate on Windows.
+pub fn normalize_rel_path(path: &str) -> String {
+    if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    }
+}
+
+// Join a /-separated relative path under a directory one segment at a time.
+pub fn join_rel_path(base: &Path, rel: &str) -> PathBuf {
+    let mut path = base.to_path_buf();
+    path.extend(rel.split('/').filter(|segment| !segment.is_empty()));
+    path
+}
+
+// Resolve a /-separated path relative to json/ on the local filesystem.
+pub fn json_path(rel: impl AsRef<str>) -> PathBuf {
+    join_rel_path(&base_json_dir(), rel.as_ref())
+}
+
+// Render a relative filesystem path with / separators, None when not valid UTF-8.
+pub fn rel_path_string(path: &Path) -> Option<String> {
+    let segments: Option<Vec<&str>> = path.components().map(|c| c.as_os_str().to_str()).collect();
+    Some(segments?.join("/"))
+}
+
+// Validate a /-separated folder path made only of safe segments.
+pub fn is_safe_dir_path(path: &str) -> bool {
+    !path.is_empty() && path.split('/').all(is_safe_segment)
+}
+
+// Validate a /-separated relative path (no traversal, prefixes or backslashes).
 pub fn is_safe_rel_path(path: &str) -> bool {
+    // A backslash is a separator on Windows and a name character elsewhere; refuse it on both.
+    if path.contains('\\') {
+        return false;
+    }
     let rel = std::path::Path::new(path);
     for component in rel.components() {
         match component {
@@ -191,126 +1647,925 @@ pub fn is_safe_rel_path(path: &str) -> bool {
     true
 }
 
-// Collect file entries and subdir names for the UI.
-pub fn collect_json_index(base_dir: PathBuf) -> (Vec<(String, String)>, Vec<String>) {
-    let entries = collect_json_entries(base_dir.clone());
-    let subdirs = collect_subdirs(base_dir);
-    (entries, subdirs)
+// A fixture file listed in the UI; metadata fields stay empty when it can't be read.
+pub struct FileEntry {
+    pub path: String,
+    pub url: String,
+    pub size: Option<u64>,
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl FileEntry {
+    // Build an entry for a json/-relative path from its metadata, if any.
+    fn new(rel_path: String, metadata: Option<std::fs::Metadata>) -> Self {
+        let url = format!("/json/{}", url_encode_path(&rel_path));
+        Self {
+            path: rel_path,
+            url,
+            size: metadata.as_ref().map(std::fs::Metadata::len),
+            modified: metadata
+                .and_then(|m| m.modified().ok())
+                .map(chrono::DateTime::<chrono::Utc>::from),
+        }
+    }
+
+    // Format the size for humans, e.g. 512 B or 1.4 KB.
+    pub fn size_label(&self) -> String {
+        let Some(size) = self.size else {
+            return String::new();
+        };
+        match size {
+            0..1024 => format!("{} B", size),
+            1024..1_048_576 => format!("{:.1} KB", size as f64 / 1024.0),
+            _ => format!("{:.1} MB", size as f64 / 1_048_576.0),
+        }
+    }
+
+    // Format the modification time as YYYY-MM-DD HH:MM UTC.
+    pub fn modified_label(&self) -> String {
+        self.modified
+            .map(|m| m.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()
+    }
+
+    // Serialize the entry for the JSON index.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path,
+            "url": self.url,
+            "size": self.size,
+            "modified": self.modified.map(|m| m.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        })
+    }
 }
 
-// Walk json/ and list all JSON file paths.
-pub fn collect_json_entries(base_dir: PathBuf) -> Vec<(String, String)> {
+// Collect file entries and subdir names for the UI, stopping at the depth and entry caps.
+pub fn collect_json_index(
+    base_dir: PathBuf,
+    max_depth: usize,
+    max_entries: usize,
+) -> (Vec<FileEntry>, Vec<String>, bool) {
     let mut entries = Vec::new();
+    let mut subdirs = Vec::new();
+    let mut truncated = false;
     if !base_dir.is_dir() {
-        return entries;
+        return (entries, subdirs, truncated);
+    }
+    let mut walker = walkdir::WalkDir::new(&base_dir)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.depth() > max_depth {
+            truncated = true;
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if entry.depth() == 1 && entry.file_type().is_dir() && entry.file_name() == SCENARIOS_FOLDER {
+            walker.skip_current_dir();
+            continue;
+        }
+        if entries.len() + subdirs.len() >= max_entries {
+            truncated = true;
+            break;
+        }
+        let Some(rel_path) = entry
+            .path()
+            .strip_prefix(&base_dir)
+            .ok()
+            .and_then(rel_path_string)
+        else {
+            continue;
+        };
+        if entry.file_type().is_dir() {
+            if is_safe_dir_path(&rel_path) {
+                subdirs.push(rel_path);
+            }
+        } else if entry.file_type().is_file() && is_safe_rel_path(&rel_path) {
+            entries.push(FileEntry::new(rel_path, entry.metadata().ok()));
+        }
     }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    subdirs.sort();
+    (entries, subdirs, truncated)
+}
+
+// List mappings whose referenced files are missing under json/.
+pub fn dangling_route_mappings(
+    mappings: &[RouteMapping],
+    base_dir: &Path,
+) -> Vec<(RouteMapping, Vec<String>)> {
+    mappings
+        .iter()
+        .filter_map(|mapping| {
+            let missing: Vec<String> = mapping
+                .referenced_files()
+                .into_iter()
+                .filter(|file| !join_rel_path(base_dir, file).is_file())
+                .map(str::to_string)
+                .collect();
+            (!missing.is_empty()).then(|| (mapping.clone(), missing))
+        })
+        .collect()
+}
+
+// Load the deepest folder level listed in the dashboard index.
+pub fn read_index_max_depth() -> usize {
+    let contents = read_config_file("index_max_depth.txt");
+    contents
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|v| *v > 0)
+        .unwrap_or(16)
+}
+
+// Load the maximum number of files and folders listed in the dashboard index.
+pub fn read_index_max_entries() -> usize {
+    let contents = read_config_file("index_max_entries.txt");
+    contents
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|v| *v > 0)
+        .unwrap_or(5000)
+}
 
+// List subdirectories under json/ recursively as /-separated relative paths.
+pub fn collect_subdirs(base_dir: PathBuf) -> Vec<String> {
+    let mut subdirs = Vec::new();
+    if !base_dir.is_dir() {
+        return subdirs;
+    }
     for entry in walkdir::WalkDir::new(&base_dir)
+        .min_depth(1)
         .follow_links(false)
         .into_iter()
         .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_type().is_dir())
     {
-        let rel_path = match entry.path().strip_prefix(&base_dir) {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
-        let rel_path_str = rel_path.to_string_lossy().replace('\\', "/");
-        if !is_safe_rel_path(&rel_path_str) {
+        let Some(rel_path) = entry
+            .path()
+            .strip_prefix(&base_dir)
+            .ok()
+            .and_then(rel_path_string)
+        else {
             continue;
+        };
+        if is_safe_dir_path(&rel_path) {
+            subdirs.push(rel_path);
         }
-        let url = format!("/json/{}", rel_path_str);
-        entries.push((rel_path_str, url));
     }
-
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
-    entries
+    subdirs.sort();
+    subdirs
 }
 
-// List immediate subdirectories under json/.
-pub fn collect_subdirs(base_dir: PathBuf) -> Vec<String> {
-    let mut subdirs = Vec::new();
+// List files inside a specific json subdirectory.
+pub fn collect_subdir_entries(base_dir: PathBuf, subdir: String) -> Vec<FileEntry> {
+    let mut entries = Vec::new();
     let Ok(read_dir) = std::fs::read_dir(base_dir) else {
-        return subdirs;
+        return entries;
     };
     for entry in read_dir.flatten() {
         let path = entry.path();
-        if path.is_dir() {
-            if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                if is_safe_segment(name) {
-                    subdirs.push(name.to_string());
+        if path.is_file()
+            && let Some(name) = path.file_name().and_then(|s| s.to_str())
+            && is_safe_segment(name)
+        {
+            let rel_path = format!("{}/{}", subdir, name);
+            entries.push(FileEntry::new(rel_path, std::fs::metadata(&path).ok()));
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+// Load POST /graphql mappings: `KEY FILE [options]` per line, KEY an operation name or query:<hash>.
+pub fn read_graphql_mappings() -> Vec<(String, RouteMapping)> {
+    let contents = read_config_file("graphql.txt");
+    let mut mappings = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(file)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if !is_safe_rel_path(file) {
+            continue;
+        }
+        let Some(options) = parse_route_options(parts) else {
+            continue;
+        };
+        let mapping = RouteMapping {
+            method: "POST".to_string(),
+            path: "/graphql".to_string(),
+            file: file.to_string(),
+            options,
+        };
+        mappings.push((key.to_string(), mapping));
+    }
+    mappings
+}
+
+// Keys a GraphQL request can be mapped by: its operationName, then query:<hash> of its query.
+pub fn graphql_operation_keys(request: &serde_json::Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(name) = request.get("operationName").and_then(|v| v.as_str())
+        && !name.is_empty()
+    {
+        keys.push(name.to_string());
+    }
+    if let Some(query) = request.get("query").and_then(|v| v.as_str()) {
+        keys.push(format!("query:{}", graphql_query_hash(query)));
+    }
+    keys
+}
+
+// Stable FNV-1a hash of a query with whitespace runs collapsed, as 16 hex digits.
+pub fn graphql_query_hash(query: &str) -> String {
+    let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in normalized.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// Load route mappings from config file.
+pub fn read_route_mappings() -> Vec<RouteMapping> {
+    let contents = read_config_file("routes.txt");
+    contents
+        .lines()
+        .filter_map(|line| parse_route_line(line).ok().flatten())
+        .collect()
+}
+
+// Parse one routes.txt line; Ok(None) for blanks and comments, Err with the reason it is skipped.
+pub fn parse_route_line(line: &str) -> Result<Option<RouteMapping>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_uppercase();
+    if method != "GET" && method != "POST" {
+        return Err(format!("method {} is not GET or POST", method));
+    }
+    let (Some(path), Some(file)) = (parts.next(), parts.next()) else {
+        return Err("expected METHOD PATH FILE [options]".to_string());
+    };
+    if !path.starts_with("/api/") || !is_safe_rel_path(path.trim_start_matches('/')) {
+        return Err(format!("path {} must be a safe path under /api/", path));
+    }
+    if !is_safe_rel_path(file) {
+        return Err(format!("file {} must be a safe path relative to json/", file));
+    }
+    let options = check_route_options(parts)?;
+    Ok(Some(RouteMapping {
+        method,
+        path: path.to_string(),
+        file: file.to_string(),
+        options,
+    }))
+}
+
+// Parse trailing key=value mapping options, rejecting malformed ones.
+pub fn parse_route_options<'a>(
+    parts: impl Iterator<Item = &'a str>,
+) -> Option<Vec<(String, String)>> {
+    check_route_options(parts).ok()
+}
+
+// Parse trailing key=value mapping options, explaining the first malformed one.
+pub fn check_route_options<'a>(
+    parts: impl Iterator<Item = &'a str>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut options = Vec::new();
+    for part in parts {
+        let Some((key, value)) = part.split_once('=') else {
+            return Err(format!("option {} is not key=value", part));
+        };
+        let invalid = |expected: &str| Err(format!("option {}: {}", part, expected));
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return invalid("key must be letters, digits or _");
+        }
+        if FILE_OPTIONS.contains(&key) && !is_safe_rel_path(value) {
+            return invalid("expected a safe path relative to json/");
+        }
+        if key == "schedule" && parse_schedule(value).is_none() {
+            return invalid("expected a HH:MM-HH:MM window");
+        }
+        if key == "tz" && value.parse::<chrono_tz::Tz>().is_err() {
+            return invalid("unknown time zone");
+        }
+        if key == "mode" && !RESPONSE_MODES.contains(&value) {
+            return invalid(&format!("expected one of {}", RESPONSE_MODES.join(", ")));
+        }
+        if (key == "interval" || key == "per_page" || key == "unsafe_truncate" || key == "max_body")
+            && value.parse::<u64>().is_err()
+        {
+            return invalid("expected a non-negative integer");
+        }
+        if key == "truncate_length" && value != "match" && value != "full" {
+            return invalid("expected match or full");
+        }
+        if key == "delay" && parse_delay_range(value).is_none() {
+            return invalid("expected milliseconds like 250 or 100-400ms");
+        }
+        if key == "content_type" && !is_media_type(value) {
+            return invalid("expected a type/subtype media type");
+        }
+        if key == "lang" && value != "negotiate" {
+            return invalid("expected negotiate");
+        }
+        if key == "pointer" && !value.starts_with('/') {
+            return invalid("expected an RFC 6901 JSON Pointer starting with /");
+        }
+        if key == "cors" && !is_valid_origin(value) {
+            return invalid("expected an origin like https://partner.example.com[:port]");
+        }
+        options.push((key.to_string(), value.to_string()));
+    }
+    let option = |key: &str| options.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    if option("loop").is_some_and(is_enabled_value)
+        && let Some(interval) = option("interval")
+        && interval.parse::<u64>().is_ok_and(|ms| ms < MIN_LOOP_INTERVAL_MS)
+    {
+        return Err(format!(
+            "option interval={}: loop=on needs at least {}ms between events",
+            interval, MIN_LOOP_INTERVAL_MS
+        ));
+    }
+    Ok(options)
+}
+
+// Shortest interval allowed for an endless loop=on stream, so it cannot flood the client.
+const MIN_LOOP_INTERVAL_MS: u64 = 10;
+
+// Check an http(s) origin: scheme, host and optional port, without path or trailing slash.
+pub fn is_valid_origin(value: &str) -> bool {
+    let Some(rest) = value.strip_prefix("https://").or_else(|| value.strip_prefix("http://")) else {
+        return false;
+    };
+    let (host_ok, port) = match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+        Some((ipv6, port)) => {
+            let port = match port {
+                "" => None,
+                port => match port.strip_prefix(':') {
+                    Some(port) => Some(port),
+                    None => return false,
+                },
+            };
+            (ipv6.parse::<std::net::Ipv6Addr>().is_ok(), port)
+        }
+        None => {
+            let (host, port) = match rest.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (rest, None),
+            };
+            let host_ok = !host.is_empty()
+                && host.split('.').all(|label| {
+                    !label.is_empty()
+                        && !label.starts_with('-')
+                        && !label.ends_with('-')
+                        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                });
+            (host_ok, port)
+        }
+    };
+    host_ok && port.is_none_or(|p| p.parse::<u16>().is_ok_and(|p| p > 0))
+}
+
+// Load the global CORS allowlist from config/cors.txt: one origin per line, or `*`.
+pub fn read_cors_origins() -> Vec<String> {
+    read_config_file("cors.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter(|line| {
+            let valid = *line == "*" || is_valid_origin(line);
+            if !valid {
+                tracing::warn!(origin = %line, "ignoring invalid origin in config/cors.txt");
+            }
+            valid
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+// Origin to allow: the mapping's cors= wins, else the request's if the allowlist has it or `*`.
+pub fn allowed_cors_origin(
+    mapping_origin: Option<&str>,
+    request_origin: Option<&str>,
+    allowlist: &[String],
+) -> Option<String> {
+    if let Some(origin) = mapping_origin {
+        return Some(origin.to_string());
+    }
+    if allowlist.iter().any(|o| o == "*") {
+        return Some("*".to_string());
+    }
+    let origin = request_origin?;
+    allowlist.iter().any(|o| o == origin).then(|| origin.to_string())
+}
+
+// Languages from an Accept-Language header, most preferred first, each tag before its primary subtag.
+pub fn accept_languages(header: &str) -> Vec<String> {
+    let mut ranked: Vec<(f32, usize, String)> = header
+        .split(',')
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            let valid = !tag.is_empty()
+                && tag != "*"
+                && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            (valid && quality > 0.0).then_some((quality, index, tag))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut languages: Vec<String> = Vec::new();
+    for (_, _, tag) in ranked {
+        let primary = tag.split('-').next().unwrap_or_default().to_string();
+        for candidate in [tag, primary] {
+            if !languages.contains(&candidate) {
+                languages.push(candidate);
+            }
+        }
+    }
+    languages
+}
+
+// Insert a language before the file extension: greeting.json -> greeting.it.json.
+pub fn language_variant(file: &str, lang: &str) -> String {
+    let name_start = file.rfind('/').map_or(0, |i| i + 1);
+    match file[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = name_start + dot;
+            format!("{}.{}{}", &file[..dot], lang, &file[dot..])
+        }
+        _ => format!("{}.{}", file, lang),
+    }
+}
+
+// Languages the HTML pages are translated into; the first is the default.
+pub const UI_LANGS: &[&str] = &["it", "en"];
+
+// Read the forced UI language from config/ui_lang.txt; empty means follow Accept-Language.
+pub fn read_ui_lang() -> Option<&'static str> {
+    let contents = read_config_file("ui_lang.txt");
+    let lang = contents.trim().to_ascii_lowercase();
+    if lang.is_empty() {
+        return None;
+    }
+    let found = UI_LANGS.iter().copied().find(|l| *l == lang);
+    if found.is_none() {
+        tracing::warn!(value = %lang, "ignoring config/ui_lang.txt, expected it or en");
+    }
+    found
+}
+
+// Pick the UI language: config/ui_lang.txt, then Accept-Language, then Italian.
+pub fn ui_lang(accept_language: Option<&str>) -> &'static str {
+    if let Some(lang) = read_ui_lang() {
+        return lang;
+    }
+    accept_language
+        .map(accept_languages)
+        .unwrap_or_default()
+        .iter()
+        .find_map(|tag| UI_LANGS.iter().copied().find(|l| l == tag))
+        .unwrap_or(UI_LANGS[0])
+}
+
+// UI strings as (key, Italian, English).
+const UI_STRINGS: &[(&str, &str, &str)] = &[
+    ("intro", "Questa app espone automaticamente i file presenti in <code>json/</code> come endpoint HTTP. Ogni file diventa raggiungibile con <code>/json/&lt;sottocartella&gt;/&lt;file&gt;</code>. Le risposte vengono lette dal disco a ogni richiesta, quindi gli aggiornamenti sono immediati.", "This app automatically exposes the files in <code>json/</code> as HTTP endpoints. Each file is reachable at <code>/json/&lt;folder&gt;/&lt;file&gt;</code>. Responses are read from disk on every request, so updates are immediate."),
+    ("author", "Autore", "Author"),
+    ("readonly", "Modalità sola lettura: le modifiche sono disabilitate.", "Read-only mode: changes are disabled."),
+    ("tab_overview", "Panoramica", "Overview"),
+    ("tab_routing", "Routing API", "API routing"),
+    ("tab_settings", "Impostazioni", "Settings"),
+    ("request_log", "Log richieste (live)", "Request log (live)"),
+    ("all_methods", "Tutti i metodi", "All methods"),
+    ("all_statuses", "Tutti gli stati", "All statuses"),
+    ("export", "Esporta", "Export"),
+    ("fs_log", "Modifiche fixture (live)", "Fixture changes (live)"),
+    ("no_changes", "Nessuna modifica recente", "No recent changes"),
+    ("active_endpoints", "Endpoint attivi", "Active endpoints"),
+    ("api_mappings", "Mappature API", "API mappings"),
+    ("missing_file", "file mancante", "missing file"),
+    ("shadow_title", "L'endpoint {} ha la precedenza su questa associazione", "The {} endpoint takes precedence over this mapping"),
+    ("shadowed_by", "oscurata da {}", "shadowed by {}"),
+    ("delete_lower", "elimina", "delete"),
+    ("no_mappings", "Nessuna mappatura configurata", "No mappings configured"),
+    ("subdirs", "Sottocartelle", "Folders"),
+    ("files", "File disponibili", "Available files"),
+    ("truncated", "Elenco troncato: mostrati al massimo {entries} elementi fino a {depth} livelli di cartelle.", "List truncated: showing at most {entries} entries down to {depth} folder levels."),
+    ("routing_intro", "Associa un endpoint <code>/api/...</code> a un file JSON in <code>json/</code>.", "Map an <code>/api/...</code> endpoint to a JSON file in <code>json/</code>."),
+    ("shadowed_count", "{} associazioni oscurate", "{} shadowed mappings"),
+    ("shadowed_hint", "ping (GET) e refresh (POST) rispondono prima delle associazioni sullo stesso metodo e path.", "ping (GET) and refresh (POST) are answered before mappings on the same method and path."),
+    ("method", "Metodo", "Method"),
+    ("route_file", "File (relativo a json/)", "File (relative to json/)"),
+    ("route_options", "Opzioni (chiave=valore separate da spazi)", "Options (space-separated key=value)"),
+    ("map", "Associa", "Map"),
+    ("bulk_label", "Importa piu associazioni (una per riga: <code>METODO PATH FILE [opzioni]</code>)", "Import several mappings (one per line: <code>METHOD PATH FILE [options]</code>)"),
+    ("bulk_submit", "Importa righe", "Import lines"),
+    ("openapi_label", "Importa da OpenAPI (YAML o JSON)", "Import from OpenAPI (YAML or JSON)"),
+    ("import", "Importa", "Import"),
+    ("active_mappings", "Associazioni attive", "Active mappings"),
+    ("no_routes", "Nessuna associazione configurata", "No mappings configured"),
+    ("scenarios", "Scenari", "Scenarios"),
+    ("scenarios_intro", "Uno scenario sostituisce il file di più associazioni in un colpo solo. Definisci le sostituzioni in <code>config/scenarios.txt</code> (<code>SCENARIO METODO PATH FILE</code>) oppure crea una cartella <code>json/_scenarios/&lt;nome&gt;/</code> con i file da sostituire.", "A scenario swaps the file of several mappings at once. Define the overrides in <code>config/scenarios.txt</code> (<code>SCENARIO METHOD PATH FILE</code>) or create a <code>json/_scenarios/&lt;name&gt;/</code> folder holding the replacement files."),
+    ("active_scenario", "Scenario attivo:", "Active scenario:"),
+    ("none", "nessuno", "none"),
+    ("activate_scenario", "Attiva scenario", "Activate scenario"),
+    ("no_scenario", "— nessuno (associazioni base) —", "— none (base mappings) —"),
+    ("apply", "Applica", "Apply"),
+    ("overrides", "Sostituzioni", "Overrides"),
+    ("no_scenarios", "Nessuno scenario configurato", "No scenarios configured"),
+    ("auth", "Autenticazione", "Authentication"),
+    ("auth_intro", "Configura l'endpoint di refresh token e usa la risposta JSON salvata su disco.", "Configure the token refresh endpoint and answer with the JSON response saved on disk."),
+    ("current_endpoint", "Endpoint attuale:", "Current endpoint:"),
+    ("set_endpoint", "Imposta un endpoint sotto <code>/api/</code>", "Set an endpoint under <code>/api/</code>"),
+    ("update", "Aggiorna", "Update"),
+    ("ping_intro", "Endpoint di check connessione che ritorna uno stato JSON.", "Connectivity check endpoint returning a JSON status."),
+    ("folders", "Gestione cartelle", "Folder management"),
+    ("folders_intro", "Crea, rinomina o elimina sottocartelle sotto <code>json/</code>.", "Create, rename or delete folders under <code>json/</code>."),
+    ("folder_name", "Nome sottocartella (anche annidata, es. <code>users/admins</code>)", "Folder name (may be nested, e.g. <code>users/admins</code>)"),
+    ("create", "Crea", "Create"),
+    ("rename_folder", "Rinomina cartella", "Rename folder"),
+    ("new_name", "nuovo_nome", "new_name"),
+    ("rename", "Rinomina", "Rename"),
+    ("delete_folder", "Elimina cartella", "Delete folder"),
+    ("confirm_name", "ripeti il nome per confermare", "repeat the name to confirm"),
+    ("delete", "Elimina", "Delete"),
+    ("log_filters", "Filtri log", "Log filters"),
+    ("log_filters_intro", "Inserisci uno per riga. Supporta match esatto, prefisso con <code>/*</code> (es. <code>/json/*</code>) o regex con <code>re:</code> (es. <code>re:\\.map$</code>).", "One per line. Supports exact matches, prefixes with <code>/*</code> (e.g. <code>/json/*</code>) or regexes with <code>re:</code> (e.g. <code>re:\\.map$</code>)."),
+    ("ignored_paths", "Path da ignorare", "Paths to ignore"),
+    ("global_log", "Log globale", "Global log"),
+    ("global_log_intro", "Abilita o disabilita completamente i log di richieste e risposte.", "Turn request and response logging on or off entirely."),
+    ("log_state", "Stato log", "Log state"),
+    ("save", "Salva", "Save"),
+    ("search_files", "Cerca file...", "Search files..."),
+    ("edit_file", "Modifica file", "Edit file"),
+    ("back_to_folder", "← torna alla cartella", "← back to folder"),
+    ("edit", "Modifica", "Edit"),
+    ("validated", "I file <code>.json</code> vengono validati prima del salvataggio.", "<code>.json</code> files are validated before saving."),
+    ("preview_file", "Anteprima file", "File preview"),
+    ("preview", "Anteprima", "Preview"),
+    ("original_file", "file originale", "original file"),
+    ("back", "← torna indietro", "← back"),
+    ("folder", "Cartella", "Folder"),
+    ("preview_lower", "anteprima", "preview"),
+    ("edit_lower", "modifica", "edit"),
+    ("confirm_delete", "Eliminare il file?", "Delete the file?"),
+    ("upload", "Upload", "Upload"),
+    ("upload_intro", "Carica uno o piu file. Verranno salvati con il nome originale.", "Upload one or more files. They are saved under their original name."),
+    ("save_as", "nome di salvataggio (solo per un file)", "save as (single file only)"),
+    ("upload_submit", "Carica", "Upload"),
+    ("move_file", "Sposta file", "Move file"),
+    ("move", "Sposta", "Move"),
+    ("copy_file", "Copia file", "Copy file"),
+    ("copy", "Copia", "Copy"),
+    ("from", "Da (relativo a json/)", "From (relative to json/)"),
+    ("to", "A (relativo a json/)", "To (relative to json/)"),
+    ("new_file", "nuovo.json", "new.json"),
+];
+
+// Look up a UI string in the given language, falling back to the key itself.
+pub fn ui_text(lang: &str, key: &'static str) -> &'static str {
+    UI_STRINGS
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map_or(key, |(_, it, en)| if lang == "en" { en } else { it })
+}
+
+// Check a media type like application/vnd.api+json or text/plain;charset=utf-8.
+pub fn is_media_type(value: &str) -> bool {
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&^_.+-".contains(c))
+    };
+    let mut parts = value.split(';');
+    let essence = parts.next().unwrap_or_default();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    is_token(kind)
+        && is_token(subtype)
+        && parts.all(|param| {
+            param
+                .split_once('=')
+                .is_some_and(|(name, value)| is_token(name) && is_token(value.trim_matches('"')))
+        })
+}
+
+// Check a request against an expect file ({"headers": {...}, "body": schema}); returns the mismatches.
+// Requests without a body (GET) only have their headers checked.
+pub fn check_expectation(
+    expect: &serde_json::Value,
+    header: impl Fn(&str) -> Option<String>,
+    body: Option<&[u8]>,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    if let Some(headers) = expect.get("headers").and_then(|v| v.as_object()) {
+        for (name, expected) in headers {
+            let expected = expected.as_str().unwrap_or("*");
+            match header(name) {
+                None => failures.push(format!("missing header {}", name)),
+                Some(actual) if expected != "*" && actual != expected => {
+                    failures.push(format!("header {} is {:?}, expected {:?}", name, actual, expected))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    if let (Some(schema), Some(body)) = (expect.get("body"), body) {
+        match serde_json::from_slice::<serde_json::Value>(body) {
+            Ok(value) => validate_schema(schema, &value, "body", &mut failures),
+            Err(_) => failures.push("body is not valid JSON".to_string()),
+        }
+    }
+    failures
+}
+
+// Validate a value against a JSON Schema subset: type, enum, const, required, properties, items.
+fn validate_schema(schema: &serde_json::Value, value: &serde_json::Value, at: &str, failures: &mut Vec<String>) {
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            serde_json::Value::Array(items) => items.iter().filter_map(|t| t.as_str()).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        let matches = |kind: &str| match kind {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !types.is_empty() && !types.iter().any(|kind| matches(kind)) {
+            failures.push(format!("{} should be {}", at, types.join(" or ")));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(|v| v.as_array())
+        && !options.contains(value)
+    {
+        failures.push(format!("{} is not one of the allowed values", at));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        failures.push(format!("{} should be {}", at, expected));
+    }
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !object.contains_key(key) {
+                    failures.push(format!("{}.{} is required", at, key));
                 }
             }
         }
+        if let Some(properties) = schema.get("properties").and_then(|v| v.as_object()) {
+            for (key, property) in properties {
+                if let Some(child) = object.get(key) {
+                    validate_schema(property, child, &format!("{}.{}", at, key), failures);
+                }
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            validate_schema(items, item, &format!("{}[{}]", at, index), failures);
+        }
+    }
+}
+
+// Count an expectation result for a mapping, keeping the latest failure reasons.
+pub fn record_assertion(method: &str, path: &str, failures: &[String]) {
+    let assertions = ASSERTIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut assertions = assertions.lock().unwrap();
+    let stats = assertions
+        .entry((method.to_string(), path.to_string()))
+        .or_default();
+    if failures.is_empty() {
+        stats.passed += 1;
+    } else {
+        stats.failed += 1;
+        stats.last_failure = failures.to_vec();
+    }
+}
+
+// Summarize expectation counters overall and per mapping.
+pub fn assertions_snapshot() -> serde_json::Value {
+    let assertions = ASSERTIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    let assertions = assertions.lock().unwrap();
+    let mut routes: Vec<_> = assertions.iter().collect();
+    routes.sort_by(|a, b| a.0.cmp(b.0));
+    let routes: Vec<serde_json::Value> = routes
+        .into_iter()
+        .map(|((method, path), stats)| {
+            serde_json::json!({
+                "method": method,
+                "path": path,
+                "passed": stats.passed,
+                "failed": stats.failed,
+                "last_failure": stats.last_failure,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "passed": assertions.values().map(|s| s.passed).sum::<u64>(),
+        "failed": assertions.values().map(|s| s.failed).sum::<u64>(),
+        "routes": routes,
+    })
+}
+
+// Parse a delay like 250, 250ms or 100-400ms into a (min, max) range in milliseconds.
+pub fn parse_delay_range(spec: &str) -> Option<(u64, u64)> {
+    let spec = spec.strip_suffix("ms").unwrap_or(spec);
+    let (min, max) = match spec.split_once('-') {
+        Some((min, max)) => (min.parse::<u64>().ok()?, max.parse::<u64>().ok()?),
+        None => {
+            let value = spec.parse::<u64>().ok()?;
+            (value, value)
+        }
+    };
+    (min <= max).then_some((min, max))
+}
+
+// Pick a delay inside a (min, max) millisecond range.
+pub fn pick_delay((min, max): (u64, u64)) -> Duration {
+    let millis = FakeRng::new(None).range(min as i64, max as i64);
+    Duration::from_millis(millis.max(0) as u64)
+}
+
+// Parse a HH:MM-HH:MM time window.
+pub fn parse_schedule(spec: &str) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
+    let (start, end) = spec.split_once('-')?;
+    let start = chrono::NaiveTime::parse_from_str(start, "%H:%M").ok()?;
+    let end = chrono::NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+    Some((start, end))
+}
+
+// Check whether the current time in the given timezone falls in the window.
+pub fn is_schedule_active(spec: &str, tz: &str) -> bool {
+    let Some((start, end)) = parse_schedule(spec) else {
+        return false;
+    };
+    let tz = tz.parse::<chrono_tz::Tz>().unwrap_or(chrono_tz::UTC);
+    let now = chrono::Utc::now().with_timezone(&tz).time();
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+// Split a fixture into SSE payloads: array elements or non-empty lines.
+pub fn parse_sse_events(bytes: &[u8]) -> Vec<String> {
+    if let Ok(serde_json::Value::Array(items)) = serde_json::from_slice(bytes) {
+        return items.iter().map(|item| item.to_string()).collect();
     }
-    subdirs.sort();
-    subdirs
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
 }
 
-// List files inside a specific json subdirectory.
-pub fn collect_subdir_entries(base_dir: PathBuf, subdir: String) -> Vec<(String, String)> {
-    let mut entries = Vec::new();
-    let Ok(read_dir) = std::fs::read_dir(base_dir) else {
-        return entries;
+// Slice a JSON array fixture into a page wrapped with total/page/per_page.
+pub fn paginate_json(bytes: &[u8], page: usize, per_page: usize, data_key: &str) -> Option<Vec<u8>> {
+    let serde_json::Value::Array(items) = serde_json::from_slice(bytes).ok()? else {
+        return None;
     };
-    for entry in read_dir.flatten() {
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                if is_safe_segment(name) {
-                    let rel_path = format!("{}/{}", subdir, name);
-                    let url = format!("/json/{}", rel_path);
-                    entries.push((rel_path, url));
-                }
-            }
-        }
-    }
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
-    entries
+    let total = items.len();
+    let start = page.saturating_sub(1).saturating_mul(per_page);
+    let data: Vec<serde_json::Value> = if page == 0 {
+        Vec::new()
+    } else {
+        items.into_iter().skip(start).take(per_page).collect()
+    };
+    let mut envelope = serde_json::Map::new();
+    envelope.insert(data_key.to_string(), serde_json::Value::Array(data));
+    envelope.insert("total".to_string(), total.into());
+    envelope.insert("page".to_string(), page.into());
+    envelope.insert("per_page".to_string(), per_page.into());
+    serde_json::to_vec(&serde_json::Value::Object(envelope)).ok()
 }
 
-// Load route mappings from config file.
-pub fn read_route_mappings() -> Vec<RouteMapping> {
-    let path = base_config_dir().join("routes.txt");
-    let contents = std::fs::read_to_string(path).unwrap_or_default();
-    let mut mappings = Vec::new();
+// Check whether a config or option value means "enabled".
+pub fn is_enabled_value(value: &str) -> bool {
+    let value = value.trim().to_lowercase();
+    value == "on" || value == "true" || value == "1"
+}
+
+// Load scenario overrides (SCENARIO METHOD PATH FILE) from config file.
+pub fn read_scenario_overrides() -> Vec<ScenarioOverride> {
+    let contents = read_config_file("scenarios.txt");
+    let mut overrides = Vec::new();
     for line in contents.lines() {
         let line = line.trim();
         if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        let mut parts = line.split_whitespace();
-        let method = match parts.next() {
-            Some(m) => m.to_uppercase(),
-            None => continue,
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let [scenario, method, path, file] = parts[..] else {
+            continue;
         };
-        if method != "GET" && method != "POST" {
+        let method = method.to_uppercase();
+        if !is_safe_segment(scenario) || (method != "GET" && method != "POST") {
             continue;
         }
-        let path = match parts.next() {
-            Some(p) => p.to_string(),
-            None => continue,
-        };
-        let file = match parts.next() {
-            Some(f) => f.to_string(),
-            None => continue,
-        };
         if !path.starts_with("/api/") || !is_safe_rel_path(path.trim_start_matches('/')) {
             continue;
         }
-        if !is_safe_rel_path(&file) {
+        if !is_safe_rel_path(file) {
             continue;
         }
-        mappings.push(RouteMapping { method, path, file });
+        overrides.push(ScenarioOverride {
+            scenario: scenario.to_string(),
+            method,
+            path: path.to_string(),
+            file: file.to_string(),
+        });
+    }
+    overrides
+}
+
+// List the distinct scenario names that have overrides.
+pub fn read_scenario_names() -> Vec<String> {
+    let mut names: Vec<String> = read_scenario_overrides()
+        .into_iter()
+        .map(|o| o.scenario)
+        .collect();
+    if let Ok(read_dir) = std::fs::read_dir(base_scenarios_dir()) {
+        for entry in read_dir.flatten() {
+            if entry.path().is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && is_safe_segment(name)
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Top-level json/ folder holding per-scenario fixture trees, kept out of listings.
+pub const SCENARIOS_FOLDER: &str = "_scenarios";
+
+// Root folder holding per-scenario fixture trees (json/_scenarios/<name>/).
+pub fn base_scenarios_dir() -> PathBuf {
+    base_json_dir().join(SCENARIOS_FOLDER)
+}
+
+// Resolve a fixture path, preferring the active scenario's folder when it has the file.
+pub fn scenario_file_path(file: &str) -> PathBuf {
+    if let Some(scenario) = read_active_scenario()
+        && is_safe_segment(&scenario)
+    {
+        let candidate = join_rel_path(&base_scenarios_dir().join(scenario), file);
+        if candidate.is_file() {
+            return candidate;
+        }
+    }
+    json_path(file)
+}
+
+// Load the active scenario name, if any.
+pub fn read_active_scenario() -> Option<String> {
+    let contents = read_config_file("scenario.txt");
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
     }
-    mappings
 }
 
 // Persist route mappings to config file.
 pub fn write_route_mappings(mappings: &[RouteMapping]) -> std::io::Result<()> {
-    let config_dir = base_config_dir();
-    std::fs::create_dir_all(&config_dir)?;
-    let path = config_dir.join("routes.txt");
     let mut out = String::new();
     for m in mappings {
         out.push_str(&m.method);
@@ -318,9 +2573,15 @@ pub fn write_route_mappings(mappings: &[RouteMapping]) -> std::io::Result<()> {
         out.push_str(&m.path);
         out.push(' ');
         out.push_str(&m.file);
+        for (key, value) in &m.options {
+            out.push(' ');
+            out.push_str(key);
+            out.push('=');
+            out.push_str(value);
+        }
         out.push('\n');
     }
-    std::fs::write(path, out)
+    write_config_file("routes.txt", out)
 }
 
 // Escape text for safe HTML rendering.
@@ -339,18 +2600,202 @@ pub fn html_escape(input: &str) -> String {
     out
 }
 
-// Initialize the in-memory log buffer and broadcaster.
+// Convert non-JSON fixture formats (CSV, YAML) to JSON bytes; other files pass through unchanged.
+pub fn convert_fixture(rel_path: &str, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    let lower = rel_path.to_lowercase();
+    if lower.ends_with(".csv") {
+        return csv_to_json(&bytes);
+    }
+    if lower.ends_with(".yaml") || lower.ends_with(".yml") {
+        return yaml_to_json(&bytes);
+    }
+    Ok(bytes)
+}
+
+// Parse a merge manifest {"key": "file relative to json/", ...} into its components.
+pub fn manifest_components(bytes: &[u8]) -> Result<Vec<(String, String)>, String> {
+    let manifest: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(bytes)
+        .map_err(|err| format!("merge manifest must be a JSON object of key: file: {}", err))?;
+    manifest
+        .into_iter()
+        .map(|(key, file)| match file.as_str() {
+            Some(file) if is_safe_rel_path(file) => Ok((key, file.to_string())),
+            _ => Err(format!("merge manifest entry {} must be a safe path under json/", key)),
+        })
+        .collect()
+}
+
+// Serve only the part of a JSON fixture an RFC 6901 pointer selects (%XX escapes decoded).
+pub fn select_json_pointer(bytes: &[u8], pointer: &str) -> Result<Vec<u8>, String> {
+    let pointer = url_decode(pointer);
+    let value: serde_json::Value = serde_json::from_slice(bytes)
+        .map_err(|err| format!("pointer {} needs a JSON fixture: {}", pointer, err))?;
+    let selected = value
+        .pointer(&pointer)
+        .ok_or_else(|| format!("pointer {} matches nothing in the fixture", pointer))?;
+    serde_json::to_vec(selected).map_err(|err| err.to_string())
+}
+
+// Parse a YAML document, resolving anchors and merge keys, and re-encode it as JSON.
+fn yaml_to_json(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_slice(bytes).map_err(|err| format!("invalid YAML: {}", err))?;
+    value
+        .apply_merge()
+        .map_err(|err| format!("invalid YAML: {}", err))?;
+    serde_json::to_vec(&value).map_err(|err| format!("YAML not representable as JSON: {}", err))
+}
+
+// Turn CSV rows into a JSON array of objects keyed by the header row.
+fn csv_to_json(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = csv::Reader::from_reader(bytes);
+    let headers = reader
+        .headers()
+        .map_err(|err| format!("invalid CSV header: {}", err))?
+        .clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| format!("invalid CSV: {}", err))?;
+        let row: serde_json::Map<String, serde_json::Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(key, value)| (key.to_string(), serde_json::Value::from(value)))
+            .collect();
+        rows.push(serde_json::Value::Object(row));
+    }
+    serde_json::to_vec(&rows).map_err(|err| err.to_string())
+}
+
+// Wrap JSON tokens in <span> classes (k key, s string, n number, b literal), escaping everything.
+pub fn highlight_json(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut out = String::with_capacity(json.len() * 2);
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let class = match chars[i] {
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                let is_key = chars[i..]
+                    .iter()
+                    .find(|c| !c.is_whitespace())
+                    .is_some_and(|c| *c == ':');
+                if is_key { "k" } else { "s" }
+            }
+            '-' | '0'..='9' => {
+                while i < chars.len() && matches!(chars[i], '0'..='9' | '-' | '+' | '.' | 'e' | 'E') {
+                    i += 1;
+                }
+                "n"
+            }
+            c if c.is_ascii_alphabetic() => {
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                "b"
+            }
+            c => {
+                out.push_str(&html_escape(&c.to_string()));
+                i += 1;
+                continue;
+            }
+        };
+        let token: String = chars[start..i].iter().collect();
+        out.push_str("<span class=\"");
+        out.push_str(class);
+        out.push_str("\">");
+        out.push_str(&html_escape(&token));
+        out.push_str("</span>");
+    }
+    out
+}
+
+// Initialize the in-memory log buffer and broadcaster, replaying persisted lines.
 pub fn init_log_state() {
-    let (sender, _) = broadcast::channel(256);
+    let (sender, _) = broadcast::channel(read_log_channel_capacity());
+    let mut buffer = VecDeque::with_capacity(256);
+    let mut persisted = 0;
+    if read_log_persist() {
+        let current = read_persisted_log(&request_log_path());
+        persisted = current.len();
+        let mut lines = read_persisted_log(&rotated_log_path());
+        lines.extend(current);
+        let skip = lines.len().saturating_sub(200);
+        buffer.extend(
+            lines
+                .into_iter()
+                .skip(skip)
+                .filter_map(|line| LogEntry::parse_line(&line)),
+        );
+    }
+    for (index, entry) in buffer.iter_mut().enumerate() {
+        entry.id = index as u64 + 1;
+    }
+    let (writer, lines) = std::sync::mpsc::channel();
+    let (current, rotated) = (request_log_path(), rotated_log_path());
+    std::thread::spawn(move || write_log_lines(lines, &current, &rotated, persisted));
     let state = LogState {
         sender,
-        buffer: Mutex::new(VecDeque::with_capacity(256)),
+        next_id: std::sync::atomic::AtomicU64::new(buffer.len() as u64 + 1),
+        buffer: Mutex::new(buffer),
+        writer,
     };
     let _ = LOG_STATE.set(state);
 }
 
+// Resolve the persisted request log path.
+fn request_log_path() -> PathBuf {
+    base_config_dir().join("request_log.txt")
+}
+
+// Resolve the rotated request log path.
+fn rotated_log_path() -> PathBuf {
+    base_config_dir().join("request_log.txt.1")
+}
+
+// Read persisted log lines from disk.
+fn read_persisted_log(path: &std::path::Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// Append queued log lines to the persisted log in order, rotating at the configured size.
+fn write_log_lines(
+    lines: std::sync::mpsc::Receiver<String>,
+    current: &Path,
+    rotated: &Path,
+    mut persisted: usize,
+) {
+    use std::io::Write;
+    for line in lines {
+        if persisted >= read_log_persist_max() {
+            let _ = std::fs::rename(current, rotated);
+            persisted = 0;
+        }
+        if let Some(parent) = current.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(current);
+        if let Ok(mut file) = file
+            && writeln!(file, "{}", line).is_ok()
+        {
+            persisted += 1;
+        }
+    }
+}
+
 // Subscribe to log events for SSE.
-pub fn subscribe_logs() -> broadcast::Receiver<String> {
+pub fn subscribe_logs() -> broadcast::Receiver<LogEntry> {
     LOG_STATE
         .get()
         .expect("log state not initialized")
@@ -358,29 +2803,90 @@ pub fn subscribe_logs() -> broadcast::Receiver<String> {
         .subscribe()
 }
 
-// Append a log line to buffer and broadcast it.
-pub fn log_line(line: String) {
+// Append a log entry to buffer and broadcast it.
+pub fn log_line(mut entry: LogEntry) {
     if let Some(state) = LOG_STATE.get() {
-        let _ = state.sender.send(line.clone());
+        let persist = read_log_persist();
+        // Number, queue for the log file and broadcast under the buffer lock so ids stay in order.
         let mut buf = state.buffer.lock().unwrap();
+        entry.id = state.next_id.fetch_add(1, Ordering::Relaxed);
+        if persist {
+            let _ = state.writer.send(entry.to_line());
+        }
+        let _ = state.sender.send(entry.clone());
         if buf.len() >= 200 {
             buf.pop_front();
         }
-        buf.push_back(line);
+        buf.push_back(entry);
     }
 }
 
 // Return a snapshot of the current log buffer.
-pub fn log_snapshot() -> Vec<String> {
+pub fn log_snapshot() -> Vec<LogEntry> {
     LOG_STATE
         .get()
         .map(|state| state.buffer.lock().unwrap().iter().cloned().collect())
         .unwrap_or_default()
 }
 
+// Subscribe to fixture change events for SSE.
+pub fn subscribe_fs_changes() -> broadcast::Receiver<FsChange> {
+    FS_STATE
+        .get()
+        .expect("fs state not initialized")
+        .sender
+        .subscribe()
+}
+
+// Return the recent fixture changes, newest first.
+pub fn fs_changes_snapshot() -> Vec<FsChange> {
+    FS_STATE
+        .get()
+        .map(|state| state.changes.lock().unwrap().iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+// Bumped on every json/ or config/ event so cached renders can tell they are stale.
+static FS_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+// Current watch generation; it changes whenever a watched file does.
+pub fn fs_generation() -> u64 {
+    FS_GENERATION.load(Ordering::Acquire)
+}
+
+// Mark everything rendered from the current files as stale, without waiting for the watcher.
+pub fn bump_fs_generation() {
+    FS_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+// Record a coalesced fs change in the bounded change ring and broadcast it.
+fn record_fs_change(path: String, kind: &str) {
+    let Some(state) = FS_STATE.get() else {
+        return;
+    };
+    bump_fs_generation();
+    let change = FsChange {
+        path,
+        kind: kind.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    };
+    let _ = state.sender.send(change.clone());
+    let mut changes = state.changes.lock().unwrap();
+    if changes.len() >= 100 {
+        changes.pop_front();
+    }
+    changes.push_back(change);
+}
+
 // Start filesystem watcher for json/ with log output.
 pub fn start_fs_watch() {
     let base_dir = base_json_dir();
+    let config_dir = base_config_dir();
+    let (sender, _) = broadcast::channel(64);
+    let _ = FS_STATE.set(FsState {
+        sender,
+        changes: Mutex::new(VecDeque::with_capacity(100)),
+    });
     std::thread::spawn(move || {
         let (tx, rx) = std::sync::mpsc::channel();
         let mut watcher = match notify::recommended_watcher(tx) {
@@ -397,25 +2903,679 @@ pub fn start_fs_watch() {
         }
 
         tracing::info!(path = %base_dir.display(), "fs watch started");
-        for event in rx {
-            match event {
-                Ok(event) => {
-                    let paths = event
-                        .paths
-                        .iter()
-                        .map(|p| p.display().to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    tracing::info!(
-                        kind = ?event.kind,
-                        paths = %paths,
-                        "fs event"
-                    );
+        let _ = std::fs::create_dir_all(&config_dir);
+        match watcher.watch(&config_dir, notify::RecursiveMode::Recursive) {
+            Ok(()) => {
+                CONFIG_WATCHED.store(true, Ordering::Release);
+                tracing::info!(path = %config_dir.display(), "config watch started");
+            }
+            Err(err) => tracing::error!(error = %err, "config watch start failed"),
+        }
+        let mut pending: Vec<(PathBuf, &'static str)> = Vec::new();
+        let mut first_pending = Instant::now();
+        loop {
+            let wait = if pending.is_empty() {
+                Duration::from_secs(3600)
+            } else {
+                FS_DEBOUNCE
+            };
+            match rx.recv_timeout(wait) {
+                Ok(Ok(event)) => {
+                    // Request log appends change no settings and would flush every cache.
+                    let only_request_log = !event.paths.is_empty()
+                        && event.paths.iter().all(|p| is_request_log_path(p, &config_dir));
+                    if !only_request_log {
+                        // Invalidate right away; only the log line waits for the debounce.
+                        if event.paths.iter().any(|p| p.starts_with(&config_dir)) {
+                            invalidate_config_cache();
+                        }
+                        bump_fs_generation();
+                        if pending.is_empty() {
+                            first_pending = Instant::now();
+                        }
+                        coalesce_fs_event(&mut pending, &event);
+                    }
+                    if pending.is_empty() || first_pending.elapsed() < FS_DEBOUNCE_MAX {
+                        continue;
+                    }
                 }
-                Err(err) => {
+                Ok(Err(err)) => {
+                    invalidate_config_cache();
+                    bump_fs_generation();
                     tracing::error!(error = %err, "fs watch error");
+                    continue;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    CONFIG_WATCHED.store(false, Ordering::Release);
+                    break;
+                }
+            }
+            for (path, kind) in pending.drain(..) {
+                if is_atomic_temp(&path) {
+                    continue;
                 }
+                if let Ok(rel) = path.strip_prefix(&config_dir) {
+                    record_config_change(&rel_path_string(rel).unwrap_or_default(), kind);
+                    continue;
+                }
+                let rel = path.strip_prefix(&base_dir).unwrap_or(&path);
+                let rel = rel_path_string(rel)
+                    .unwrap_or_else(|| rel.to_string_lossy().replace('\\', "/"));
+                tracing::info!(kind = kind, path = %rel, "fs change");
+                if read_log_enabled() {
+                    let mut entry = LogEntry::new("FS");
+                    entry.detail = Some(format!("{} {}", change_verb(kind), rel));
+                    log_line(entry);
+                }
+                record_fs_change(rel, kind);
             }
         }
     });
 }
+
+// Past-tense verb for a coalesced change kind, as shown in the live log.
+fn change_verb(kind: &str) -> &'static str {
+    match kind {
+        "create" => "created",
+        "delete" => "deleted",
+        _ => "modified",
+    }
+}
+
+// Whether a config/-relative name is the persisted request log or one of its rotations.
+fn is_request_log_file(rel: &str) -> bool {
+    rel.starts_with("request_log.txt")
+}
+
+// Whether a watched path is the persisted request log under config/.
+fn is_request_log_path(path: &Path, config_dir: &Path) -> bool {
+    path.strip_prefix(config_dir)
+        .ok()
+        .and_then(rel_path_string)
+        .is_some_and(|rel| is_request_log_file(&rel))
+}
+
+// Log a config file change so manual edits are visibly picked up.
+fn record_config_change(rel: &str, kind: &str) {
+    // The persisted request log lives in config/ too; logging its writes would loop.
+    if rel.is_empty() || is_request_log_file(rel) {
+        return;
+    }
+    tracing::info!(kind = kind, path = %rel, "config change");
+    if read_log_enabled() {
+        let mut entry = LogEntry::new("CONFIG");
+        entry.detail = Some(format!("{} {}", change_verb(kind), rel));
+        log_line(entry);
+    }
+}
+
+// Quiet period after the last raw event before a batch of fs changes is flushed.
+const FS_DEBOUNCE: Duration = Duration::from_millis(200);
+
+// Upper bound on how long a continuous burst of events is held back.
+const FS_DEBOUNCE_MAX: Duration = Duration::from_secs(1);
+
+// Merge a raw watcher event into the pending changes, keeping one entry per path.
+fn coalesce_fs_event(pending: &mut Vec<(PathBuf, &'static str)>, event: &notify::Event) {
+    let kind = match event.kind {
+        notify::EventKind::Create(_) => "create",
+        notify::EventKind::Modify(_) => "modify",
+        notify::EventKind::Remove(_) => "delete",
+        _ => return,
+    };
+    for path in &event.paths {
+        match pending.iter_mut().find(|(p, _)| p == path) {
+            Some((_, existing)) => {
+                *existing = match (*existing, kind) {
+                    ("create", "modify") => "create",
+                    ("delete", "create") => "modify",
+                    (_, next) => next,
+                };
+            }
+            None => pending.push((path.clone(), kind)),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Serializes tests that point the server at a temp root, since the root is process-wide.
+    static ROOT_LOCK: Mutex<()> = Mutex::new(());
+
+    // A temp root in use by the current test; other root-using tests wait until it drops.
+    pub(crate) struct TempRoot {
+        path: PathBuf,
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl std::ops::Deref for TempRoot {
+        type Target = PathBuf;
+
+        fn deref(&self) -> &PathBuf {
+            &self.path
+        }
+    }
+
+    // Names in a directory that look like atomic write leftovers.
+    fn temp_leftovers(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| is_atomic_temp(path))
+            .collect()
+    }
+
+    // Point the server at a fresh temp root with empty json/ and config/ folders.
+    pub(crate) fn use_temp_root() -> TempRoot {
+        static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let lock = ROOT_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let root = std::env::temp_dir().join(format!(
+            "apifilestub-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("json")).unwrap();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        set_root_dir(root.clone());
+        TempRoot { path: root, _lock: lock }
+    }
+
+    #[test]
+    fn config_cache_loads_each_file_once() {
+        let root = use_temp_root();
+        write_config_file("routes.txt", "GET /api/a a.json\n").unwrap();
+        CONFIG_WATCHED.store(true, Ordering::Release);
+        assert_eq!(read_route_mappings().len(), 1);
+
+        // Written behind the cache's back: the cached copy keeps answering.
+        std::fs::write(root.join("config/routes.txt"), "").unwrap();
+        for _ in 0..10 {
+            assert_eq!(read_route_mappings().len(), 1);
+        }
+
+        write_config_file("routes.txt", "GET /api/a a.json\nGET /api/b b.json\n").unwrap();
+        assert_eq!(read_route_mappings().len(), 2);
+    }
+
+    #[test]
+    fn config_load_racing_an_invalidation_is_not_cached() {
+        let _root = use_temp_root();
+        write_config_file("ping_endpoint.txt", "/api/old").unwrap();
+        CONFIG_WATCHED.store(true, Ordering::Release);
+        let racing = |name: &str| {
+            let contents = load_config_file(name);
+            invalidate_config_cache();
+            contents
+        };
+        assert_eq!(read_config_with("ping_endpoint.txt", racing), "/api/old");
+
+        // Written behind the cache's back: only an uncached entry can see it.
+        std::fs::write(base_config_dir().join("ping_endpoint.txt"), "/api/new").unwrap();
+        assert_eq!(read_ping_endpoint(), "/api/new");
+    }
+
+    #[test]
+    fn request_log_writes_are_not_config_changes() {
+        let config_dir = PathBuf::from("/srv/config");
+        assert!(is_request_log_path(&config_dir.join("request_log.txt"), &config_dir));
+        assert!(is_request_log_path(&config_dir.join("request_log.txt.1"), &config_dir));
+        assert!(!is_request_log_path(&config_dir.join("routes.txt"), &config_dir));
+        assert!(!is_request_log_path(Path::new("/srv/json/request_log.txt"), &config_dir));
+    }
+
+    // Request headers for check_expectation from (name, value) pairs, case-insensitively.
+    fn headers_from<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            pairs
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn expectation_without_body_checks_only_headers() {
+        let expect = serde_json::json!({
+            "headers": {"authorization": "*"},
+            "body": {"type": "object", "required": ["name"]}
+        });
+        let auth = [("Authorization", "Bearer x")];
+        assert!(check_expectation(&expect, headers_from(&auth), None).is_empty());
+        assert_eq!(
+            check_expectation(&expect, headers_from(&[]), None),
+            vec!["missing header authorization"]
+        );
+    }
+
+    #[test]
+    fn expectation_checks_header_values_and_body() {
+        let expect = serde_json::json!({
+            "headers": {"x-tenant": "acme"},
+            "body": {"type": "object", "required": ["name"]}
+        });
+        let good = [("X-Tenant", "acme")];
+        let body = br#"{"name":"a"}"#;
+        assert!(check_expectation(&expect, headers_from(&good), Some(body)).is_empty());
+
+        let bad = [("X-Tenant", "other")];
+        let failures = check_expectation(&expect, headers_from(&bad), Some(b"{}"));
+        assert_eq!(
+            failures,
+            vec![
+                "header x-tenant is \"other\", expected \"acme\"".to_string(),
+                "body.name is required".to_string(),
+            ]
+        );
+        assert_eq!(
+            check_expectation(&expect, headers_from(&good), Some(b"not json")),
+            vec!["body is not valid JSON"]
+        );
+        assert_eq!(
+            check_expectation(&expect, headers_from(&good), Some(b"")),
+            vec!["body is not valid JSON"]
+        );
+    }
+
+    // Run validate_schema and return its failures.
+    fn schema_failures(schema: serde_json::Value, value: serde_json::Value) -> Vec<String> {
+        let mut failures = Vec::new();
+        validate_schema(&schema, &value, "body", &mut failures);
+        failures
+    }
+
+    #[test]
+    fn schema_types() {
+        use serde_json::json;
+        assert!(schema_failures(json!({"type": "integer"}), json!(3)).is_empty());
+        assert_eq!(schema_failures(json!({"type": "integer"}), json!(3.5)), vec!["body should be integer"]);
+        assert!(schema_failures(json!({"type": "number"}), json!(3.5)).is_empty());
+        assert!(schema_failures(json!({"type": ["string", "null"]}), json!(null)).is_empty());
+        assert_eq!(
+            schema_failures(json!({"type": ["string", "null"]}), json!(1)),
+            vec!["body should be string or null"]
+        );
+        assert!(schema_failures(json!({"type": "unknown"}), json!(1)).is_empty());
+    }
+
+    #[test]
+    fn schema_enum_and_const() {
+        use serde_json::json;
+        assert!(schema_failures(json!({"enum": ["a", "b"]}), json!("a")).is_empty());
+        assert_eq!(
+            schema_failures(json!({"enum": ["a", "b"]}), json!("c")),
+            vec!["body is not one of the allowed values"]
+        );
+        assert_eq!(schema_failures(json!({"const": 1}), json!(2)), vec!["body should be 1"]);
+    }
+
+    #[test]
+    fn schema_nested_properties_and_items() {
+        use serde_json::json;
+        let schema = json!({
+            "type": "object",
+            "required": ["users"],
+            "properties": {
+                "users": {"type": "array", "items": {"type": "object", "required": ["id"],
+                    "properties": {"id": {"type": "integer"}}}}
+            }
+        });
+        assert!(schema_failures(schema.clone(), json!({"users": [{"id": 1}]})).is_empty());
+        assert_eq!(
+            schema_failures(schema.clone(), json!({"users": [{"id": "1"}, {}]})),
+            vec!["body.users[0].id should be integer", "body.users[1].id is required"]
+        );
+        assert_eq!(schema_failures(schema, json!({})), vec!["body.users is required"]);
+    }
+
+    #[test]
+    fn join_rel_path_splits_on_slashes() {
+        let base = Path::new("base");
+        let joined = join_rel_path(base, "users/admins/list.json");
+        let expected: PathBuf = ["base", "users", "admins", "list.json"].iter().collect();
+        assert_eq!(joined, expected);
+        assert_eq!(join_rel_path(base, "a//b/"), base.join("a").join("b"));
+        assert_eq!(rel_path_string(&expected), Some("base/users/admins/list.json".to_string()));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn normalize_rel_path_turns_backslashes_into_slashes() {
+        assert_eq!(normalize_rel_path(r"users\admins\list.json"), "users/admins/list.json");
+        assert_eq!(json_path(normalize_rel_path(r"ev\h.json")), json_path("ev/h.json"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn normalize_rel_path_keeps_backslashes_elsewhere() {
+        assert_eq!(normalize_rel_path(r"users\list.json"), r"users\list.json");
+        assert_eq!(normalize_rel_path("users/list.json"), "users/list.json");
+    }
+
+    #[test]
+    fn safe_rel_paths() {
+        assert!(is_safe_rel_path("users/list.json"));
+        assert!(!is_safe_rel_path(r"users\list.json"));
+        assert!(!is_safe_rel_path("../secret.json"));
+        assert!(!is_safe_rel_path("/etc/passwd"));
+    }
+
+    #[test]
+    fn dangling_mappings_resolve_nested_files() {
+        let root = use_temp_root();
+        std::fs::create_dir_all(root.join("json/users")).unwrap();
+        std::fs::write(root.join("json/users/all.json"), "[]").unwrap();
+        let present = parse_route_line("GET /api/users users/all.json").unwrap().unwrap();
+        let missing = parse_route_line("GET /api/gone users/gone.json").unwrap().unwrap();
+        let dangling = dangling_route_mappings(&[present, missing], &base_json_dir());
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].1, vec!["users/gone.json"]);
+    }
+
+    #[test]
+    fn request_ids_are_uuid_v4() {
+        let ids: std::collections::HashSet<String> = (0..1000).map(|_| new_request_id()).collect();
+        assert_eq!(ids.len(), 1000);
+        for id in &ids {
+            let parts: Vec<&str> = id.split('-').collect();
+            let lengths: Vec<usize> = parts.iter().map(|p| p.len()).collect();
+            assert_eq!(lengths, vec![8, 4, 4, 4, 12]);
+            assert!(parts[2].starts_with('4'));
+            assert!(matches!(parts[3].chars().next(), Some('8' | '9' | 'a' | 'b')));
+            assert!(is_valid_request_id(id));
+        }
+    }
+
+    #[test]
+    fn mapping_cors_origin_beats_the_global_allowlist() {
+        let allowlist = vec!["https://app.example.com".to_string()];
+        let partner = Some("https://partner.example.com");
+        assert_eq!(
+            allowed_cors_origin(partner, Some("https://app.example.com"), &allowlist).as_deref(),
+            partner
+        );
+        assert_eq!(
+            allowed_cors_origin(None, Some("https://app.example.com"), &allowlist).as_deref(),
+            Some("https://app.example.com")
+        );
+        assert_eq!(allowed_cors_origin(None, Some("https://evil.example"), &allowlist), None);
+        assert_eq!(allowed_cors_origin(None, None, &allowlist), None);
+        let any = vec!["*".to_string()];
+        assert_eq!(allowed_cors_origin(None, Some("https://x.example"), &any).as_deref(), Some("*"));
+        assert_eq!(allowed_cors_origin(None, None, &[]), None);
+    }
+
+    #[test]
+    fn global_cors_allowlist_skips_invalid_origins() {
+        let _root = use_temp_root();
+        write_config_file("cors.txt", "https://app.example.com\nnot-an-origin\n*\n").unwrap();
+        assert_eq!(read_cors_origins(), vec!["https://app.example.com", "*"]);
+    }
+
+    #[test]
+    fn origins() {
+        assert!(is_valid_origin("https://partner.example.com"));
+        assert!(is_valid_origin("http://localhost:5173"));
+        assert!(is_valid_origin("http://[::1]:8080"));
+        assert!(!is_valid_origin("https://partner.example.com/"));
+        assert!(!is_valid_origin("ftp://example.com"));
+        assert!(!is_valid_origin("http://localhost:0"));
+        assert!(!is_valid_origin("https://-x.com"));
+    }
+
+    #[test]
+    fn path_patterns_compile_each_regex_once() {
+        // Holding a root keeps other tests from invalidating the shared regex cache meanwhile.
+        let _root = use_temp_root();
+        let patterns = vec![
+            "re:^/assets/.+\\.map$".to_string(),
+            "/json/*".to_string(),
+            "/health".to_string(),
+        ];
+        assert!(matches_path_pattern(patterns.clone(), "/assets/app.js.map"));
+        assert!(matches_path_pattern(patterns.clone(), "/json/ev/h.json"));
+        assert!(matches_path_pattern(patterns.clone(), "/json"));
+        assert!(matches_path_pattern(patterns.clone(), "/health"));
+        assert!(!matches_path_pattern(patterns.clone(), "/healthz"));
+
+        // Swap the cached entry: a pattern served from the cache follows the swap.
+        let cache = REGEX_CACHE.get().unwrap();
+        let compiled = cache.lock().unwrap().get("^/assets/.+\\.map$").cloned();
+        assert!(compiled.is_some_and(|re| re.is_some()));
+        cache
+            .lock()
+            .unwrap()
+            .insert("^/assets/.+\\.map$".to_string(), regex::Regex::new("^/swapped$").ok());
+        assert!(!matches_path_pattern(patterns.clone(), "/assets/app.js.map"));
+        assert!(matches_path_pattern(patterns.clone(), "/swapped"));
+
+        invalidate_config_cache();
+        assert!(matches_path_pattern(patterns, "/assets/app.js.map"));
+        assert!(!matches_path_pattern(vec!["re:(".to_string()], "/anything"));
+    }
+
+    #[test]
+    fn etag_matching() {
+        let etag = "\"abc\"";
+        assert!(etag_matches("\"abc\"", etag));
+        assert!(etag_matches("W/\"abc\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(etag_matches("\"x\", \"abc\"", etag));
+        assert!(etag_matches("\"x\",W/\"abc\"", etag));
+        assert!(!etag_matches("\"abd\"", etag));
+        assert!(!etag_matches("abc", etag));
+        assert!(!etag_matches("\"x\", \"y\"", etag));
+        assert!(!etag_matches("", etag));
+    }
+
+    #[test]
+    fn etag_follows_bytes_and_mtime() {
+        let time = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let etag = compute_etag(b"{}", Some(time));
+        assert_eq!(etag, compute_etag(b"{}", Some(time)));
+        assert_ne!(etag, compute_etag(b"[]", Some(time)));
+        assert_ne!(etag, compute_etag(b"{}", Some(time + Duration::from_secs(1))));
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+    }
+
+    #[test]
+    fn url_decode_escapes_anywhere() {
+        assert_eq!(url_decode("%2Fstart"), "/start");
+        assert_eq!(url_decode("mid%20dle"), "mid dle");
+        assert_eq!(url_decode("end%3F"), "end?");
+        assert_eq!(url_decode("%41%42%43"), "ABC");
+        assert_eq!(url_decode("a+b"), "a b");
+        assert_eq!(url_decode("lower%2fcase"), "lower/case");
+    }
+
+    #[test]
+    fn url_decode_keeps_invalid_or_truncated_escapes() {
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(url_decode("%4"), "%4");
+        assert_eq!(url_decode("%zz"), "%zz");
+        assert_eq!(url_decode("%%41"), "%A");
+        assert_eq!(url_decode("50%-off"), "50%-off");
+    }
+
+    #[test]
+    fn url_decode_multibyte_utf8() {
+        assert_eq!(url_decode("citt%C3%A0"), "città");
+        assert_eq!(url_decode("%E2%82%AC%20price"), "€ price");
+        assert_eq!(url_decode_bytes("%F0%9F%98%80"), "😀".as_bytes());
+        // A lone continuation byte is not UTF-8 and becomes the replacement character.
+        assert_eq!(url_decode("a%A0b"), "a\u{FFFD}b");
+        assert_eq!(url_decode_bytes("a%A0b"), vec![b'a', 0xA0, b'b']);
+    }
+
+    #[test]
+    fn url_encode_path_round_trips_non_ascii_names() {
+        for name in ["città.json", "ev/città #1.json", "a b/c?d&e.json", "日本/データ.json"] {
+            let encoded = url_encode_path(name);
+            assert!(encoded.is_ascii(), "{} encoded as {}", name, encoded);
+            assert_eq!(url_decode(&encoded), name);
+        }
+        assert_eq!(url_encode_path("città.json"), "citt%C3%A0.json");
+        assert_eq!(url_encode_path("ev/h.json"), "ev/h.json");
+    }
+
+    #[test]
+    fn failed_atomic_write_keeps_old_contents() {
+        let root = use_temp_root();
+        let target = root.join("json/data.json");
+        write_atomic(&target, br#"{"version":1}"#).unwrap();
+
+        // Write half of the new contents, then fail as a crash mid-write would.
+        let result = write_atomic_with(&target, |file| {
+            use std::io::Write;
+            file.write_all(br#"{"version":2,"#)?;
+            Err(std::io::Error::other("simulated crash while writing"))
+        });
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&target).unwrap(), br#"{"version":1}"#);
+        assert!(temp_leftovers(&root.join("json")).is_empty());
+
+        write_atomic(&target, br#"{"version":3}"#).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), br#"{"version":3}"#);
+        assert!(temp_leftovers(&root.join("json")).is_empty());
+    }
+
+    #[test]
+    fn atomic_write_into_missing_folder_fails_cleanly() {
+        let root = use_temp_root();
+        assert!(write_atomic(&root.join("json/missing/data.json"), b"{}").is_err());
+        assert!(!root.join("json/missing").exists());
+        assert!(temp_leftovers(&root.join("json")).is_empty());
+
+        // A parent that is a file fails the temp file creation; the file is untouched.
+        std::fs::write(root.join("json/plain.json"), "{}").unwrap();
+        assert!(write_atomic(&root.join("json/plain.json/data.json"), b"[]").is_err());
+        assert_eq!(std::fs::read(root.join("json/plain.json")).unwrap(), b"{}");
+        assert!(temp_leftovers(&root.join("json")).is_empty());
+    }
+
+    #[test]
+    fn comment_and_blank_lines_are_never_active() {
+        let _root = use_temp_root();
+        write_config_file(
+            "routes.txt",
+            "# GET /api/old old.json\n\n   # POST /api/items items.json\nGET /api/items items.json\n \t\n",
+        )
+        .unwrap();
+        let routes = read_route_mappings();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].method, "GET");
+        assert_eq!(routes[0].path, "/api/items");
+
+        write_config_file("log_ignore.txt", "# /api/secret\n\n  #re:^/api/.*\n/api/health\n").unwrap();
+        let patterns = read_log_ignore_patterns();
+        assert!(patterns.iter().all(|pattern| !pattern.contains('#')));
+        assert_eq!(patterns.last().map(String::as_str), Some("/api/health"));
+        assert_eq!(patterns.len(), 5);
+
+        write_config_file("ping_endpoint.txt", "# /api/old/ping\n\n/api/health/ping\n").unwrap();
+        assert_eq!(read_ping_endpoint(), "/api/health/ping");
+        write_config_file("ping_endpoint.txt", "# /api/old/ping\n\n").unwrap();
+        assert_eq!(read_ping_endpoint(), "/api/v1/ping");
+        write_config_file("refresh_endpoint.txt", "\n  # /api/old/refresh\n").unwrap();
+        assert_eq!(read_refresh_endpoint(), "/api/v1/authentication/refresh");
+    }
+
+    #[test]
+    fn looping_streams_need_a_minimum_interval() {
+        let line = |options: &str| parse_route_line(&format!("GET /api/feed feed.json mode=sse {}", options));
+        assert!(line("loop=on interval=0").err().unwrap().contains("at least 10ms"));
+        assert!(line("interval=9 loop=true").is_err());
+        assert!(line("loop=on interval=10").unwrap().is_some());
+        assert!(line("loop=on").unwrap().is_some());
+        assert!(line("loop=off interval=0").unwrap().is_some());
+        assert!(line("interval=0").unwrap().is_some());
+    }
+
+    #[test]
+    fn sse_events_from_arrays_and_lines() {
+        assert_eq!(parse_sse_events(br#"[{"id":1}, "two", 3]"#), [r#"{"id":1}"#, r#""two""#, "3"]);
+        assert!(parse_sse_events(b"[]").is_empty());
+        assert_eq!(parse_sse_events(b"first\n\n  second  \r\n\t\nthird"), ["first", "second", "third"]);
+        // A JSON value that is not an array is treated as plain lines.
+        assert_eq!(parse_sse_events(b"{\"a\": 1}\n{\"b\": 2}"), [r#"{"a": 1}"#, r#"{"b": 2}"#]);
+        assert_eq!(parse_sse_events(b"ok\n\xffbad"), ["ok", "\u{fffd}bad"]);
+        assert!(parse_sse_events(b" \n\n").is_empty());
+    }
+
+    #[test]
+    fn client_address_prefers_reachable_loopback() {
+        let addrs = |list: &[&str]| list.iter().map(|addr| addr.to_string()).collect::<Vec<_>>();
+        assert_eq!(client_address(&addrs(&["0.0.0.0:8080"])), "127.0.0.1:8080");
+        assert_eq!(client_address(&addrs(&["[::]:8080"])), "127.0.0.1:8080");
+        assert_eq!(client_address(&addrs(&["192.168.1.5:3000", "[::1]:3001"])), "[::1]:3001");
+        assert_eq!(client_address(&addrs(&["192.168.1.5:3000", "10.0.0.2:3000"])), "192.168.1.5:3000");
+        assert_eq!(client_address(&addrs(&["10.0.0.2:3000", "0.0.0.0:3002"])), "127.0.0.1:3002");
+        assert_eq!(client_address(&[]), "127.0.0.1:3000");
+    }
+
+    #[test]
+    fn log_writer_appends_in_order_and_rotates() {
+        let root = use_temp_root();
+        write_config_file("log_persist_max.txt", "3").unwrap();
+        let current = root.join("config/request_log.txt");
+        let rotated = root.join("config/request_log.txt.1");
+        std::fs::write(&current, "old 1\nold 2\n").unwrap();
+        let write = |range: std::ops::RangeInclusive<u32>, persisted: usize| {
+            let (writer, lines) = std::sync::mpsc::channel();
+            for index in range {
+                writer.send(format!("line {}", index)).unwrap();
+            }
+            drop(writer);
+            write_log_lines(lines, &current, &rotated, persisted);
+        };
+
+        write(1..=4, 2);
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "old 1\nold 2\nline 1\n");
+        assert_eq!(std::fs::read_to_string(&current).unwrap(), "line 2\nline 3\nline 4\n");
+
+        write(5..=5, 3);
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "line 2\nline 3\nline 4\n");
+        assert_eq!(std::fs::read_to_string(&current).unwrap(), "line 5\n");
+    }
+
+    #[test]
+    fn paginate_json_edges() {
+        let page = |bytes: &[u8], page: usize, per_page: usize| -> serde_json::Value {
+            serde_json::from_slice(&paginate_json(bytes, page, per_page, "data").unwrap()).unwrap()
+        };
+        let items = b"[1, 2, 3, 4, 5]";
+        assert_eq!(
+            page(items, 2, 2),
+            serde_json::json!({ "data": [3, 4], "total": 5, "page": 2, "per_page": 2 })
+        );
+        assert_eq!(page(items, 3, 2)["data"], serde_json::json!([5]));
+        // Page 0 and pages past the end are empty but still report the total.
+        assert_eq!(page(items, 0, 2)["data"], serde_json::json!([]));
+        assert_eq!(page(items, 0, 2)["total"], 5);
+        assert_eq!(page(items, 4, 2)["data"], serde_json::json!([]));
+        assert_eq!(page(items, usize::MAX, usize::MAX)["data"], serde_json::json!([]));
+        assert_eq!(page(b"[]", 1, 10)["total"], 0);
+        assert!(paginate_json(b"{\"a\": 1}", 1, 10, "data").is_none());
+        assert!(paginate_json(b"not json", 1, 10, "data").is_none());
+    }
+
+    #[test]
+    fn rate_limit_window_resets() {
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        let window = Duration::from_millis(80);
+
+        assert_eq!(check_rate_limit(client, 2, window), Ok(1));
+        assert_eq!(check_rate_limit(client, 2, window), Ok(0));
+        let reset = check_rate_limit(client, 2, window).unwrap_err();
+        assert!(reset <= window);
+        // Each IP has its own window.
+        assert_eq!(check_rate_limit(other, 2, window), Ok(1));
+
+        std::thread::sleep(window + Duration::from_millis(20));
+        assert_eq!(check_rate_limit(client, 2, window), Ok(1));
+    }
+}